@@ -0,0 +1,181 @@
+use crate::{
+    decode::{decode_gif_frames, decode_webp_frames, ImageFormat},
+    texture::TextureImage,
+    FetchError,
+};
+use eframe::egui;
+use std::time::Duration;
+
+// Like browsers, treat (nearly) zero delays as the usual 100 ms.
+const MIN_DELAY: Duration = Duration::from_millis(20);
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// Frames of an animated image with their delays, and where the playback is at.
+///
+/// Animations loop forever whatever loop count the file asks for.
+/// Cloning is cheap, the textures are shared.
+#[derive(Clone)]
+pub struct Animation {
+    frames: Vec<(TextureImage, Duration)>,
+    current: usize,
+    playing: bool,
+    // `egui` time the current frame started being shown at.
+    shown_at: Option<f64>,
+}
+
+impl Animation {
+    /// Animations are cut after this many frames, every one of them is a texture.
+    pub const MAX_FRAMES: usize = 256;
+    /// And once their decoded pixels add up to this many bytes.
+    pub const MAX_BYTES: usize = 256 * 1024 * 1024;
+
+    /// `None` without any frame.
+    pub fn new(frames: Vec<(TextureImage, Duration)>) -> Option<Self> {
+        if frames.is_empty() {
+            return None;
+        }
+        Some(Self {
+            frames,
+            current: 0,
+            playing: true,
+            shown_at: None,
+        })
+    }
+
+    /// Decode every frame of a GIF or WebP and upload them,
+    /// `None` if it isn't animated or `format` can't be.
+    pub fn decode(
+        ctx: &egui::Context,
+        format: ImageFormat,
+        debug_name: &str,
+        bytes: &[u8],
+    ) -> Result<Option<(Self, egui::ColorImage)>, FetchError> {
+        match format {
+            ImageFormat::Gif => Self::decode_gif(ctx, debug_name, bytes),
+            ImageFormat::Webp => Self::decode_webp(ctx, debug_name, bytes),
+            _ => Ok(None),
+        }
+    }
+
+    /// Decode every frame of a GIF and upload them, `None` if it isn't animated.
+    ///
+    /// The first frame is named `debug_name`, the next ones get their index appended.
+    pub fn decode_gif(
+        ctx: &egui::Context,
+        debug_name: &str,
+        bytes: &[u8],
+    ) -> Result<Option<(Self, egui::ColorImage)>, FetchError> {
+        let frames = decode_gif_frames(bytes, Self::MAX_FRAMES, Self::MAX_BYTES)?;
+        Ok(Self::upload(ctx, debug_name, frames))
+    }
+
+    /// Same as [`decode_gif`](Self::decode_gif) for a WebP.
+    pub fn decode_webp(
+        ctx: &egui::Context,
+        debug_name: &str,
+        bytes: &[u8],
+    ) -> Result<Option<(Self, egui::ColorImage)>, FetchError> {
+        let frames = decode_webp_frames(bytes, Self::MAX_FRAMES, Self::MAX_BYTES)?;
+        Ok(Self::upload(ctx, debug_name, frames))
+    }
+
+    // `None` for a single frame, it's shown as a still image.
+    fn upload(
+        ctx: &egui::Context,
+        debug_name: &str,
+        frames: Vec<(egui::ColorImage, Duration)>,
+    ) -> Option<(Self, egui::ColorImage)> {
+        if frames.len() < 2 {
+            return None;
+        }
+        let first = frames[0].0.clone();
+        let frames = frames
+            .into_iter()
+            .enumerate()
+            .map(|(i, (pixels, delay))| {
+                let name = match i {
+                    0 => debug_name.to_owned(),
+                    _ => format!("{}#{}", debug_name, i),
+                };
+                (TextureImage::from_color_image(ctx, name, pixels), delay)
+            })
+            .collect();
+        Self::new(frames).map(|animation| (animation, first))
+    }
+
+    /// The frame to show now.
+    pub fn frame(&self) -> &TextureImage {
+        &self.frames[self.current].0
+    }
+
+    pub fn first(&self) -> &TextureImage {
+        &self.frames[0].0
+    }
+
+    /// Every frame, in order.
+    pub fn frames(&self) -> impl Iterator<Item = &TextureImage> {
+        self.frames.iter().map(|(frame, _)| frame)
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Index of the frame to show now.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn set_playing(&mut self, playing: bool) {
+        self.playing = playing;
+        self.shown_at = None;
+    }
+
+    /// Show the next (or previous) frame and pause.
+    pub fn step(&mut self, forward: bool) {
+        self.set_playing(false);
+        self.current = if forward {
+            (self.current + 1) % self.len()
+        } else {
+            (self.current + self.len() - 1) % self.len()
+        };
+    }
+
+    /// Move on to the frame due at `now` (`egui` time, in seconds),
+    /// returns how long until the next one while playing.
+    pub fn advance(&mut self, now: f64) -> Option<Duration> {
+        if !self.playing {
+            return None;
+        }
+        // Don't replay every missed frame after a long while without repaint.
+        let total: f64 = (0..self.len()).map(|i| self.delay(i).as_secs_f64()).sum();
+        let shown_at = self.shown_at.get_or_insert(now);
+        if now - *shown_at > total {
+            *shown_at = now;
+        }
+        loop {
+            let delay = self.delay(self.current).as_secs_f64();
+            let shown_at = self.shown_at.as_mut()?;
+            if now - *shown_at < delay {
+                return Some(Duration::from_secs_f64(delay - (now - *shown_at)));
+            }
+            *shown_at += delay;
+            self.current = (self.current + 1) % self.frames.len();
+        }
+    }
+
+    fn delay(&self, frame: usize) -> Duration {
+        match self.frames[frame].1 {
+            delay if delay < MIN_DELAY => DEFAULT_DELAY,
+            delay => delay,
+        }
+    }
+}
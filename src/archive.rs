@@ -0,0 +1,239 @@
+use crate::{
+    decode::ImageFormat, fetcher::fetch_data, priority::Priority, progress::ProgressSink,
+    utils::Channel, AsyncFetcher, CancelReason,
+};
+use async_trait::async_trait;
+use flowync::{error::Compact, CompactFlower, CompactHandle};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufWriter, Seek, Write},
+    path::PathBuf,
+};
+use tokio::time;
+use zip::{result::ZipResult, write::FileOptions, CompressionMethod, ZipWriter};
+
+/// Name of the manifest of every archive, listing its URLs and what became of them.
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// What became of one URL of an archive, see [`write_zip`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ManifestEntry {
+    pub url: String,
+    /// Name of the image in the archive, `None` if it couldn't be fetched.
+    pub file: Option<String>,
+    pub bytes: usize,
+    /// Why it couldn't be fetched.
+    pub error: Option<String>,
+}
+
+/// Write every fetched image of `images` to a ZIP archive, named after its URL
+/// (see [`entry_name`]), with a [`MANIFEST_NAME`] listing all of them, failed ones included.
+///
+/// Images are stored as downloaded, their formats are compressed already.
+pub fn write_zip<W: Write + Seek>(
+    writer: W,
+    images: Vec<(String, Result<Vec<u8>, String>)>,
+) -> ZipResult<Vec<ManifestEntry>> {
+    let mut zip = ZipWriter::new(writer);
+    let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut taken = HashSet::new();
+    let mut manifest = Vec::with_capacity(images.len());
+    for (url, result) in images {
+        let entry = match result {
+            Ok(bytes) => {
+                let name = unique_name(entry_name(&url, &bytes), &mut taken);
+                zip.start_file(name.as_str(), stored)?;
+                zip.write_all(&bytes)?;
+                ManifestEntry {
+                    url,
+                    file: Some(name),
+                    bytes: bytes.len(),
+                    error: None,
+                }
+            }
+            Err(error) => ManifestEntry {
+                url,
+                file: None,
+                bytes: 0,
+                error: Some(error),
+            },
+        };
+        manifest.push(entry);
+    }
+    let json = serde_json::to_vec_pretty(&manifest).map_err(io::Error::from)?;
+    zip.start_file(MANIFEST_NAME, FileOptions::default())?;
+    zip.write_all(&json)?;
+    zip.finish()?;
+    Ok(manifest)
+}
+
+/// Name of the image at `url` in an archive: its path past the host, e.g. `seed-42-512.jpg`
+/// for a picsum seed, with the extension of the format sniffed from `bytes`.
+pub fn entry_name(url: &str, bytes: &[u8]) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let path = match path.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+        None => path,
+    };
+    let segments: Vec<_> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(sanitize)
+        .collect();
+    let mut name = if segments.is_empty() {
+        "image".to_owned()
+    } else {
+        segments.join("-")
+    };
+    if let Some(format) = ImageFormat::sniff(bytes) {
+        let extension = format!(".{}", format.extension());
+        if !name.to_ascii_lowercase().ends_with(&extension) {
+            name.push_str(&extension);
+        }
+    }
+    name
+}
+
+// Keep file name friendly characters only, the rest of a path segment becomes `_`.
+fn sanitize(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+// `name`, numbered before its extension if an image of the archive has it already.
+fn unique_name(name: String, taken: &mut HashSet<String>) -> String {
+    let (stem, extension) = name.split_at(name.rfind('.').unwrap_or(name.len()));
+    let mut candidate = name.clone();
+    let mut number = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{}-{}{}", stem, number, extension);
+        number += 1;
+    }
+    candidate
+}
+
+type ArchiveHandle = CompactHandle<Channel, Vec<ManifestEntry>, String>;
+
+/// State of an [`ArchiveJob`] returned by [`ArchiveJob::poll`].
+pub enum ArchiveState {
+    Idle,
+    /// Carrying download progress as [`Channel::Data`], if any since the last poll.
+    Running(Option<Channel>),
+    /// The archive is written, with the manifest of every URL.
+    Done(Result<Vec<ManifestEntry>, String>),
+}
+
+/// Downloads several images as is and packs them in a ZIP archive, see [`write_zip`].
+///
+/// Images are fetched one after another as background work, sharing the fetcher's limiter
+/// and client. One that fails is listed in the manifest, canceling stops the whole export
+/// and nothing is written.
+pub struct ArchiveJob {
+    flower: CompactFlower<Channel, Vec<ManifestEntry>, String>,
+}
+
+// Reports the bytes of every image like a raw download does.
+struct ArchiveSink<'a>(&'a ArchiveHandle);
+
+#[async_trait]
+impl ProgressSink for ArchiveSink<'_> {
+    async fn on_bytes(&self, chunk_len: usize) {
+        self.0.send_async(Channel::Data(chunk_len)).await;
+    }
+
+    async fn on_total(&self, _total: usize) {}
+
+    fn should_cancel(&self) -> bool {
+        self.0.should_cancel()
+    }
+}
+
+impl ArchiveJob {
+    pub fn new() -> Self {
+        Self {
+            flower: CompactFlower::new(1),
+        }
+    }
+
+    /// Start downloading every URL in `urls`, then write them to a ZIP archive at `path`.
+    pub fn spawn(&self, fetcher: &AsyncFetcher, urls: Vec<String>, path: PathBuf) {
+        let handle = self.flower.handle();
+        handle.activate();
+        let limiter = fetcher.limiter();
+        let priority = fetcher.priority.clone();
+        let client = fetcher.client.clone();
+        let config = fetcher.config().clone();
+        fetcher.runtime_handle().spawn(async move {
+            let sink = ArchiveSink(&handle);
+            let mut images = Vec::with_capacity(urls.len());
+            for url in urls {
+                let _permit = priority
+                    .acquire(limiter.clone(), Priority::Background)
+                    .await;
+                let fetch = fetch_data(url.clone(), &client, &config, &sink);
+                let result = match time::timeout(config.deadline, fetch).await {
+                    Ok(result) => result,
+                    Err(_) => Err(CancelReason::Timeout(config.deadline).into()),
+                };
+                if handle.should_cancel() {
+                    return handle.error("Export canceled.".into());
+                }
+                images.push((url, result.map_err(|e| e.to_string())));
+            }
+            let write = move || {
+                let file = File::create(&path)
+                    .map_err(|e| format!("Unable to create {}: {}", path.display(), e))?;
+                write_zip(BufWriter::new(file), images)
+                    .map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+            };
+            match tokio::task::spawn_blocking(write).await {
+                Ok(Ok(manifest)) => handle.success(manifest),
+                Ok(Err(e)) => handle.error(e),
+                Err(e) => handle.error(e.to_string()),
+            }
+        });
+    }
+
+    /// Stop the export, the images downloaded so far are dropped.
+    pub fn cancel(&self) {
+        self.flower.cancel();
+    }
+
+    /// Check if the export is still running.
+    pub fn is_active(&self) -> bool {
+        self.flower.is_active()
+    }
+
+    /// Poll the export, should be called once per frame.
+    pub fn poll(&self) -> ArchiveState {
+        if !self.flower.is_active() {
+            return ArchiveState::Idle;
+        }
+        let mut progress = None;
+        let finalizer = self.flower.extract(|message| progress = Some(message));
+        if progress.is_some() {
+            // Leave the result to the next poll, so the last bytes are counted.
+            return ArchiveState::Running(progress);
+        }
+        let mut state = ArchiveState::Running(None);
+        finalizer.finalize(|result| {
+            state = ArchiveState::Done(result.map_err(|e| match e {
+                Compact::Suppose(e) | Compact::Panicked(e) => e,
+            }))
+        });
+        state
+    }
+}
+
+impl Default for ArchiveJob {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{
+    fetcher::{fetch_image, validate_image},
+    priority::Priority,
+    progress::ProgressSink,
+    utils::Container,
+    AsyncFetcher, CancelReason, FetchError,
+};
+use async_trait::async_trait;
+use flowync::{error::Compact, CompactFlower, CompactHandle};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::time;
+
+/// How often aggregate progress is reported while a batch runs.
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Aggregate progress of a running batch.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BatchProgress {
+    /// Images finished so far, successfully or not.
+    pub done: usize,
+    pub total: usize,
+    /// Bytes downloaded over every image.
+    pub bytes: usize,
+}
+
+/// Outcome of one image of a batch.
+#[derive(Debug)]
+pub struct BatchItem {
+    /// The requested URL, or the final one (after redirects) on success.
+    pub url: String,
+    /// Downloaded size in bytes, or the announced one when validating (0 if unknown).
+    pub result: Result<usize, FetchError>,
+}
+
+/// State of a [`BatchJob`] returned by [`BatchJob::poll`].
+pub enum BatchState {
+    Idle,
+    /// Carrying the latest aggregate progress received since the last poll (if any).
+    Running(Option<BatchProgress>),
+    /// Every image is done, in the order they were requested.
+    Done(Result<Vec<BatchItem>, String>),
+}
+
+type BatchHandle = CompactHandle<BatchProgress, Vec<BatchItem>, String>;
+
+/// Downloads several images at once, sharing the fetcher's limiter, client and cache.
+///
+/// Images wait for their turn as background work, fetches the user starts meanwhile go first.
+///
+/// Progress of every image is aggregated and reported as a whole, canceling stops the whole batch.
+pub struct BatchJob {
+    flower: CompactFlower<BatchProgress, Vec<BatchItem>, String>,
+}
+
+// Counts the bytes of one image, and of the whole batch.
+struct ItemSink {
+    handle: Arc<BatchHandle>,
+    bytes: AtomicUsize,
+    batch_bytes: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl ProgressSink for ItemSink {
+    async fn on_bytes(&self, chunk_len: usize) {
+        self.bytes.fetch_add(chunk_len, Ordering::Relaxed);
+        self.batch_bytes.fetch_add(chunk_len, Ordering::Relaxed);
+    }
+
+    async fn on_total(&self, _total: usize) {}
+
+    fn should_cancel(&self) -> bool {
+        self.handle.should_cancel()
+    }
+}
+
+impl BatchJob {
+    pub fn new() -> Self {
+        Self {
+            flower: CompactFlower::new(1),
+        }
+    }
+
+    /// Start fetching every URL in `urls`, with the fetcher's current config.
+    pub fn spawn(&self, fetcher: &AsyncFetcher, urls: Vec<String>) {
+        self.spawn_items(fetcher, urls, false);
+    }
+
+    /// Only check that every URL in `urls` is an image, nothing is decoded nor cached.
+    ///
+    /// See [`validate_image`], progress bytes stay at zero.
+    pub fn spawn_validate(&self, fetcher: &AsyncFetcher, urls: Vec<String>) {
+        self.spawn_items(fetcher, urls, true);
+    }
+
+    fn spawn_items(&self, fetcher: &AsyncFetcher, urls: Vec<String>, validate: bool) {
+        let handle = self.flower.handle();
+        handle.activate();
+        let handle = Arc::new(handle);
+        let total = urls.len();
+        let done = Arc::new(AtomicUsize::new(0));
+        let batch_bytes = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = urls
+            .into_iter()
+            .map(|url| {
+                let handle = handle.clone();
+                let done = done.clone();
+                let batch_bytes = batch_bytes.clone();
+                let limiter = fetcher.limiter();
+                let priority = fetcher.priority.clone();
+                let client = fetcher.client.clone();
+                let cache = fetcher.cache.clone();
+                let mut config = fetcher.config().clone();
+                // Nothing shows previews of batch items.
+                config.progressive_preview = false;
+                let ctx = fetcher.ctx.clone();
+                fetcher.runtime_handle().spawn(async move {
+                    let _permit = priority.acquire(limiter, Priority::Background).await;
+                    let sink = ItemSink {
+                        handle,
+                        bytes: AtomicUsize::new(0),
+                        batch_bytes,
+                    };
+                    let item = if sink.should_cancel() {
+                        BatchItem {
+                            url,
+                            result: Err(CancelReason::UserRequested.into()),
+                        }
+                    } else if validate {
+                        let check = validate_image(url.clone(), &client, &config);
+                        match time::timeout(config.deadline, check).await {
+                            Ok(Ok(validation)) => BatchItem {
+                                url: validation.url,
+                                result: Ok(validation.size.unwrap_or(0)),
+                            },
+                            Ok(Err(e)) => BatchItem {
+                                url,
+                                result: Err(e),
+                            },
+                            Err(_) => BatchItem {
+                                url,
+                                result: Err(CancelReason::Timeout(config.deadline).into()),
+                            },
+                        }
+                    } else {
+                        let fetch =
+                            fetch_image(url.clone(), &*client, &cache, &config, &ctx, &sink, None);
+                        let result = match time::timeout(config.deadline, fetch).await {
+                            Ok(result) => result,
+                            Err(_) => Err(CancelReason::Timeout(config.deadline).into()),
+                        };
+                        let bytes = sink.bytes.load(Ordering::Relaxed);
+                        match result {
+                            Ok(Container::Image(image, ..)) => BatchItem {
+                                url: image.debug_name().to_owned(),
+                                result: Ok(bytes),
+                            },
+                            Ok(_) => BatchItem {
+                                url,
+                                result: Ok(bytes),
+                            },
+                            Err(e) => BatchItem {
+                                url,
+                                result: Err(e),
+                            },
+                        }
+                    };
+                    done.fetch_add(1, Ordering::SeqCst);
+                    item
+                })
+            })
+            .collect();
+
+        fetcher.runtime_handle().spawn(async move {
+            let progress = || BatchProgress {
+                done: done.load(Ordering::SeqCst),
+                total,
+                bytes: batch_bytes.load(Ordering::Relaxed),
+            };
+            let mut interval = time::interval(REPORT_INTERVAL);
+            while progress().done < total {
+                interval.tick().await;
+                handle.send_async(progress()).await;
+            }
+            let mut items = Vec::with_capacity(total);
+            for task in tasks {
+                match task.await {
+                    Ok(item) => items.push(item),
+                    Err(e) => return handle.error(e.to_string()),
+                }
+            }
+            handle.success(items);
+        });
+    }
+
+    /// Stop the whole batch, images still running finish as canceled.
+    pub fn cancel(&self) {
+        self.flower.cancel();
+    }
+
+    /// Check if the batch is still running.
+    pub fn is_active(&self) -> bool {
+        self.flower.is_active()
+    }
+
+    /// Poll the batch, should be called once per frame.
+    pub fn poll(&self) -> BatchState {
+        if !self.flower.is_active() {
+            return BatchState::Idle;
+        }
+        let mut state = BatchState::Running(None);
+        self.flower
+            .extract(|progress| state = BatchState::Running(Some(progress)))
+            .finalize(|result| {
+                state = BatchState::Done(result.map_err(|e| match e {
+                    Compact::Suppose(e) | Compact::Panicked(e) => e,
+                }))
+            });
+        state
+    }
+}
+
+impl Default for BatchJob {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{animation::Animation, texture::TextureImage, utils::Container};
+use eframe::egui::ColorImage;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+/// A decoded image kept with the validators it was served with.
+#[derive(Clone)]
+pub struct CacheEntry {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub image: TextureImage,
+    pub pixels: ColorImage,
+    pub hash: u64,
+    // Every frame when the image is animated, `image` is the first one.
+    pub animation: Option<Animation>,
+}
+
+impl CacheEntry {
+    /// The result of a fetch answered with `304 Not Modified`.
+    pub fn container(&self, known_hash: Option<u64>) -> Container {
+        match &self.animation {
+            _ if known_hash == Some(self.hash) => Container::Unchanged,
+            Some(animation) => {
+                Container::Animation(animation.clone(), self.pixels.clone(), self.hash)
+            }
+            None => Container::Image(self.image.clone(), self.pixels.clone(), self.hash),
+        }
+    }
+}
+
+/// The bytes of a download cut short, resumed with a `Range` request by the next fetch.
+#[derive(Clone)]
+pub struct PartialDownload {
+    pub bytes: Vec<u8>,
+    /// `ETag` (or else `Last-Modified`) of the interrupted response, sent as `If-Range`.
+    pub validator: Option<String>,
+}
+
+/// Per-URL cache honoring `ETag`/`Last-Modified`, shared by every fetch of an [`AsyncFetcher`].
+///
+/// Responses without validators are stored too, they're only used in offline mode.
+/// The oldest entry is dropped past [`MAX_ENTRIES`](Self::MAX_ENTRIES).
+/// Interrupted downloads are kept as well, up to [`MAX_PARTIALS`](Self::MAX_PARTIALS).
+///
+/// [`AsyncFetcher`]: crate::AsyncFetcher
+#[derive(Default)]
+pub struct HttpCache {
+    inner: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    by_url: HashMap<String, CacheEntry>,
+    // Insertion order, oldest first.
+    order: VecDeque<String>,
+    // Oldest first as well.
+    partials: VecDeque<(String, PartialDownload)>,
+}
+
+impl HttpCache {
+    pub const MAX_ENTRIES: usize = 32;
+    pub const MAX_PARTIALS: usize = 4;
+
+    pub fn get(&self, url: &str) -> Option<CacheEntry> {
+        self.inner.lock().unwrap().by_url.get(url).cloned()
+    }
+
+    pub fn insert(&self, url: String, entry: CacheEntry) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.by_url.insert(url.clone(), entry).is_none() {
+            inner.order.push_back(url);
+            if inner.order.len() > Self::MAX_ENTRIES {
+                if let Some(oldest) = inner.order.pop_front() {
+                    inner.by_url.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    /// Only keep the entries of the URLs `keep` returns `true` for.
+    pub fn retain(&self, mut keep: impl FnMut(&str) -> bool) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.by_url.retain(|url, _| keep(url));
+        let Entries { by_url, order, .. } = &mut *inner;
+        order.retain(|url| by_url.contains_key(url));
+    }
+
+    /// Keep what `url` downloaded before failing, replacing an older partial download of it.
+    pub fn save_partial(&self, url: String, partial: PartialDownload) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.partials.retain(|(saved, _)| *saved != url);
+        inner.partials.push_back((url, partial));
+        if inner.partials.len() > Self::MAX_PARTIALS {
+            inner.partials.pop_front();
+        }
+    }
+
+    /// Remove and return the partial download of `url`, to resume it.
+    pub fn take_partial(&self, url: &str) -> Option<PartialDownload> {
+        let mut inner = self.inner.lock().unwrap();
+        let index = inner.partials.iter().position(|(saved, _)| saved == url)?;
+        inner.partials.remove(index).map(|(_, partial)| partial)
+    }
+
+    /// Bytes already downloaded of `url`, if it was cut short.
+    pub fn partial_len(&self, url: &str) -> Option<usize> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .partials
+            .iter()
+            .find(|(saved, _)| saved == url)
+            .map(|(_, partial)| partial.bytes.len())
+    }
+
+    /// Every texture held by the cached images, animation frames included.
+    pub fn textures(&self) -> Vec<TextureImage> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .by_url
+            .values()
+            .flat_map(|entry| match &entry.animation {
+                Some(animation) => animation.frames().cloned().collect(),
+                None => vec![entry.image.clone()],
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().by_url.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.by_url.clear();
+        inner.order.clear();
+        inner.partials.clear();
+    }
+}
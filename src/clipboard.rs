@@ -0,0 +1,57 @@
+use crate::data_uri::is_data_uri;
+use eframe::egui::ColorImage;
+use reqwest::Url;
+
+/// What the clipboard holds, read by the app with `arboard`.
+pub enum ClipboardContent {
+    Text(String),
+    /// Unmultiplied RGBA pixels, row by row.
+    Image {
+        size: [usize; 2],
+        rgba: Vec<u8>,
+    },
+    Empty,
+}
+
+/// What pasting [`ClipboardContent`] does.
+pub enum Paste {
+    /// Fetch it like a typed in URL, `data:` URIs included.
+    Url(String),
+    /// Show the pixels as is, there's nothing to decode.
+    Image(ColorImage),
+    /// Nothing usable, with a message saying why.
+    Unsupported(&'static str),
+}
+
+impl Paste {
+    pub fn from_content(content: ClipboardContent) -> Self {
+        match content {
+            ClipboardContent::Text(text) => {
+                let text = text.trim();
+                if text.is_empty() {
+                    return Paste::Unsupported("The clipboard is empty.");
+                }
+                if is_data_uri(text) || is_web_url(text) {
+                    Paste::Url(text.to_owned())
+                } else {
+                    Paste::Unsupported("The clipboard text isn't an image URL.")
+                }
+            }
+            ClipboardContent::Image { size, rgba } => {
+                if size[0] == 0 || size[1] == 0 || rgba.len() != size[0] * size[1] * 4 {
+                    return Paste::Unsupported("The clipboard image is empty or malformed.");
+                }
+                Paste::Image(ColorImage::from_rgba_unmultiplied(size, &rgba))
+            }
+            ClipboardContent::Empty => Paste::Unsupported("The clipboard is empty."),
+        }
+    }
+}
+
+// Only http(s) URLs with a host can be fetched.
+fn is_web_url(text: &str) -> bool {
+    match Url::parse(text) {
+        Ok(url) => matches!(url.scheme(), "http" | "https") && url.host().is_some(),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{decode::ImageFormat, FetchError};
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+
+// Standard alphabet, padded or not: both are found in the wild.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Whether `url` is a `data:` URI, carrying its content instead of pointing to it.
+pub fn is_data_uri(url: &str) -> bool {
+    url.get(..5)
+        .map_or(false, |scheme| scheme.eq_ignore_ascii_case("data:"))
+}
+
+/// Content of a `data:[<media type>][;base64],<data>` URI, decoded without any request.
+pub struct DataUri {
+    /// Media type without its parameters, `text/plain` when left out.
+    pub media_type: String,
+    pub bytes: Vec<u8>,
+}
+
+impl DataUri {
+    /// Decode `uri`, percent escapes included, failing with [`FetchError::InvalidDataUri`].
+    pub fn parse(uri: &str) -> Result<Self, FetchError> {
+        let invalid = |reason: &str| FetchError::InvalidDataUri(reason.to_owned());
+        if !is_data_uri(uri) {
+            return Err(invalid("missing the data: scheme"));
+        }
+        let (header, data) = uri[5..]
+            .split_once(',')
+            .ok_or_else(|| invalid("missing the ',' before the data"))?;
+        let mut params = header.split(';').map(str::trim);
+        let media_type = match params.next() {
+            Some("") | None => "text/plain".to_owned(),
+            Some(media_type) => media_type.to_ascii_lowercase(),
+        };
+        let base64 = params.any(|param| param.eq_ignore_ascii_case("base64"));
+        let data = percent_decode(data).ok_or_else(|| invalid("malformed percent escape"))?;
+        let bytes = if base64 {
+            let data: Vec<u8> = data
+                .into_iter()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            BASE64
+                .decode(data)
+                .map_err(|e| FetchError::InvalidDataUri(format!("bad base64 data: {}", e)))?
+        } else {
+            data
+        };
+        if bytes.is_empty() {
+            return Err(invalid("no data"));
+        }
+        Ok(Self { media_type, bytes })
+    }
+
+    /// Image format from the media type, or sniffed from the data when it isn't an image one.
+    pub fn format(&self) -> Option<ImageFormat> {
+        ImageFormat::from_content_type(&self.media_type).or_else(|| ImageFormat::sniff(&self.bytes))
+    }
+
+    /// Short name to show instead of the whole URI, e.g. `data:image/png`.
+    pub fn name(&self) -> String {
+        format!("data:{}", self.media_type)
+    }
+}
+
+// `None` on a `%` not followed by two hex digits.
+fn percent_decode(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    Some(bytes)
+}
@@ -0,0 +1,413 @@
+use crate::{
+    filter::{from_rgba_image, to_rgba_image},
+    FetchError,
+};
+use eframe::egui::ColorImage;
+use image::{DynamicImage, ImageOutputFormat};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    io::Cursor,
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+/// Image formats the fetcher knows how to decode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Svg,
+    Webp,
+    Gif,
+}
+
+impl ImageFormat {
+    pub const ALL: [Self; 5] = [Self::Jpeg, Self::Png, Self::Svg, Self::Webp, Self::Gif];
+
+    /// Formats decoded images can be saved as, see [`encode`](Self::encode).
+    pub const ENCODABLE: [Self; 3] = [Self::Png, Self::Jpeg, Self::Webp];
+
+    /// Pick the format from a `Content-Type` header value.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        if content_type.contains("image/jpeg") {
+            Some(Self::Jpeg)
+        } else if content_type.contains("image/png") {
+            Some(Self::Png)
+        } else if content_type.contains("image/svg+xml") {
+            Some(Self::Svg)
+        } else if content_type.contains("image/webp") {
+            Some(Self::Webp)
+        } else if content_type.contains("image/gif") {
+            Some(Self::Gif)
+        } else {
+            None
+        }
+    }
+
+    /// Recognize the format from the first bytes of the data.
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(Self::Jpeg)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            Some(Self::Webp)
+        } else {
+            // SVG is text, look for the root element past the XML prolog and comments.
+            let head = &bytes[..bytes.len().min(1024)];
+            let text = String::from_utf8_lossy(head);
+            let text = text.trim_start_matches('\u{feff}').trim_start();
+            (text.starts_with('<') && text.contains("<svg")).then(|| Self::Svg)
+        }
+    }
+
+    /// Pick the format from a file name extension.
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let extension = Path::new(name).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            "svg" => Some(Self::Svg),
+            "webp" => Some(Self::Webp),
+            "gif" => Some(Self::Gif),
+            _ => None,
+        }
+    }
+
+    /// The usual file name extension.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Svg => "svg",
+            Self::Webp => "webp",
+            Self::Gif => "gif",
+        }
+    }
+
+    /// The MIME type of the format, as used in `Content-Type` and `Accept` headers.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Svg => "image/svg+xml",
+            Self::Webp => "image/webp",
+            Self::Gif => "image/gif",
+        }
+    }
+
+    /// The cargo feature enabling the decoder, `None` when it's always built in.
+    pub fn feature(self) -> Option<&'static str> {
+        match self {
+            Self::Jpeg => Some("jpeg"),
+            Self::Png => Some("png"),
+            Self::Svg => None,
+            Self::Webp => Some("webp"),
+            Self::Gif => Some("gif"),
+        }
+    }
+
+    /// Whether the format may hold several frames, see [`Animation::decode`].
+    ///
+    /// [`Animation::decode`]: crate::animation::Animation::decode
+    pub fn can_animate(self) -> bool {
+        matches!(self, Self::Gif | Self::Webp)
+    }
+
+    /// Check if the decoder was compiled in.
+    pub fn is_available(self) -> bool {
+        match self {
+            Self::Jpeg => cfg!(feature = "jpeg"),
+            Self::Png => cfg!(feature = "png"),
+            Self::Svg => true,
+            Self::Webp => cfg!(feature = "webp"),
+            Self::Gif => cfg!(feature = "gif"),
+        }
+    }
+
+    /// Formats with a decoder compiled in.
+    pub fn available() -> impl Iterator<Item = Self> {
+        Self::ALL.into_iter().filter(|format| format.is_available())
+    }
+
+    /// Decode `bytes` into RGBA pixels.
+    ///
+    /// `svg_size` is the size SVG images are rasterized to fit in (aspect ratio kept),
+    /// `None` keeps their natural size. Raster formats ignore it.
+    pub fn decode(
+        self,
+        bytes: &[u8],
+        svg_size: Option<[u32; 2]>,
+    ) -> Result<ColorImage, FetchError> {
+        if let (false, Some(feature)) = (self.is_available(), self.feature()) {
+            return Err(FetchError::UnsupportedFormat {
+                mime_type: self.mime_type(),
+                feature,
+            });
+        }
+        match self {
+            // Only the first frame of an animation, see `Animation::decode` for all of them.
+            Self::Jpeg | Self::Png | Self::Webp | Self::Gif => {
+                egui_extras::image::load_image_bytes(bytes)
+            }
+            Self::Svg => load_svg_bytes(bytes, svg_size),
+        }
+        .map_err(|message| FetchError::Decode {
+            bytes: bytes.len(),
+            message,
+        })
+    }
+}
+
+/// Encode RGBA pixels as `format`, this is blocking work.
+///
+/// `jpeg_quality` (1 to 100) only applies to JPEG, WebP is encoded lossless.
+/// JPEG has no alpha channel, it's dropped.
+pub fn encode(
+    image: &ColorImage,
+    format: ImageFormat,
+    jpeg_quality: u8,
+) -> Result<Vec<u8>, String> {
+    if let (false, Some(feature)) = (format.is_available(), format.feature()) {
+        return Err(format!(
+            "No encoder for {}, build with the `{}` feature enabled",
+            format.mime_type(),
+            feature
+        ));
+    }
+    let output = match format {
+        #[cfg(feature = "png")]
+        ImageFormat::Png => ImageOutputFormat::Png,
+        #[cfg(feature = "jpeg")]
+        ImageFormat::Jpeg => ImageOutputFormat::Jpeg(jpeg_quality.clamp(1, 100)),
+        #[cfg(feature = "webp")]
+        ImageFormat::Webp => ImageOutputFormat::WebP,
+        _ => return Err(format!("Saving as {} isn't supported", format.mime_type())),
+    };
+    let mut rgba = DynamicImage::ImageRgba8(to_rgba_image(image));
+    if format == ImageFormat::Jpeg {
+        rgba = DynamicImage::ImageRgb8(rgba.to_rgb8());
+    }
+    let mut bytes = Vec::new();
+    rgba.write_to(&mut Cursor::new(&mut bytes), output)
+        .map_err(|e| format!("Unable to encode as {}: {}", format.mime_type(), e))?;
+    Ok(bytes)
+}
+
+/// Decode `bytes` straight to a thumbnail fitting in `max_dim` x `max_dim`, aspect ratio kept.
+///
+/// Only the small copy outlives the call, for previews of many images. This is blocking work.
+pub fn decode_thumbnail(bytes: &[u8], max_dim: u32) -> Result<ColorImage, FetchError> {
+    let format = ImageFormat::sniff(bytes)
+        .ok_or_else(|| FetchError::UnsupportedContentType("unrecognized data".into()))?;
+    if format == ImageFormat::Svg {
+        // Rasterized at the right size right away.
+        return format.decode(bytes, Some([max_dim, max_dim]));
+    }
+    if let (false, Some(feature)) = (format.is_available(), format.feature()) {
+        return Err(FetchError::UnsupportedFormat {
+            mime_type: format.mime_type(),
+            feature,
+        });
+    }
+    let image = image::load_from_memory(bytes).map_err(|e| FetchError::Decode {
+        bytes: bytes.len(),
+        message: e.to_string(),
+    })?;
+    Ok(shrink(image, max_dim))
+}
+
+/// Downscale already decoded pixels, like [`decode_thumbnail`].
+pub fn thumbnail(image: &ColorImage, max_dim: u32) -> ColorImage {
+    shrink(DynamicImage::ImageRgba8(to_rgba_image(image)), max_dim)
+}
+
+// Fit `image` in `max_dim` x `max_dim`, smaller images are left as is.
+fn shrink(image: DynamicImage, max_dim: u32) -> ColorImage {
+    let image = if image.width() > max_dim || image.height() > max_dim {
+        // A fast integer filter, plenty for a thumbnail.
+        image.thumbnail(max_dim, max_dim)
+    } else {
+        image
+    };
+    from_rgba_image(&image.to_rgba8())
+}
+
+/// Decode up to `max_frames` frames of a GIF with their delays,
+/// the ones past `max_bytes` of pixels in total are left out.
+///
+/// Frames are composited, each one is the full picture with the disposal methods applied.
+#[cfg(feature = "gif")]
+pub fn decode_gif_frames(
+    bytes: &[u8],
+    max_frames: usize,
+    max_bytes: usize,
+) -> Result<Vec<(ColorImage, Duration)>, FetchError> {
+    use image::{codecs::gif::GifDecoder, AnimationDecoder};
+    let decoder = GifDecoder::new(std::io::Cursor::new(bytes)).map_err(frame_error(bytes))?;
+    collect_frames(bytes, decoder.into_frames(), max_frames, max_bytes)
+}
+
+#[cfg(not(feature = "gif"))]
+pub fn decode_gif_frames(
+    _bytes: &[u8],
+    _max_frames: usize,
+    _max_bytes: usize,
+) -> Result<Vec<(ColorImage, Duration)>, FetchError> {
+    Err(FetchError::UnsupportedFormat {
+        mime_type: ImageFormat::Gif.mime_type(),
+        feature: "gif",
+    })
+}
+
+/// Same as [`decode_gif_frames`] for an animated WebP, a still one gives no frame at all.
+#[cfg(feature = "webp")]
+pub fn decode_webp_frames(
+    bytes: &[u8],
+    max_frames: usize,
+    max_bytes: usize,
+) -> Result<Vec<(ColorImage, Duration)>, FetchError> {
+    use image::{codecs::webp::WebPDecoder, AnimationDecoder};
+    let decoder = WebPDecoder::new(std::io::Cursor::new(bytes)).map_err(frame_error(bytes))?;
+    if !decoder.has_animation() {
+        return Ok(Vec::new());
+    }
+    collect_frames(bytes, decoder.into_frames(), max_frames, max_bytes)
+}
+
+#[cfg(not(feature = "webp"))]
+pub fn decode_webp_frames(
+    _bytes: &[u8],
+    _max_frames: usize,
+    _max_bytes: usize,
+) -> Result<Vec<(ColorImage, Duration)>, FetchError> {
+    Err(FetchError::UnsupportedFormat {
+        mime_type: ImageFormat::Webp.mime_type(),
+        feature: "webp",
+    })
+}
+
+#[cfg(any(feature = "gif", feature = "webp"))]
+fn frame_error(bytes: &[u8]) -> impl Fn(image::ImageError) -> FetchError {
+    let len = bytes.len();
+    move |e| FetchError::Decode {
+        bytes: len,
+        message: e.to_string(),
+    }
+}
+
+// The first frame is always kept, a still decode would cost as much.
+#[cfg(any(feature = "gif", feature = "webp"))]
+fn collect_frames(
+    bytes: &[u8],
+    frames: image::Frames<'_>,
+    max_frames: usize,
+    max_bytes: usize,
+) -> Result<Vec<(ColorImage, Duration)>, FetchError> {
+    let mut decoded = Vec::new();
+    let mut total_bytes = 0;
+    for frame in frames.take(max_frames) {
+        let frame = frame.map_err(frame_error(bytes))?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = Duration::from_millis((numer / denom.max(1)) as u64);
+        let buffer = frame.into_buffer();
+        total_bytes += buffer.as_raw().len();
+        if total_bytes > max_bytes && !decoded.is_empty() {
+            break;
+        }
+        let size = [buffer.width() as usize, buffer.height() as usize];
+        decoded.push((
+            ColorImage::from_rgba_unmultiplied(size, buffer.as_raw()),
+            delay,
+        ));
+    }
+    Ok(decoded)
+}
+
+/// Turns downloaded bytes into RGBA pixels, `svg_size` is passed along for vector formats.
+pub type DecodeFn =
+    Arc<dyn Fn(&[u8], Option<[u32; 2]>) -> Result<ColorImage, FetchError> + Send + Sync>;
+
+/// Decoders by MIME type, [`fetch_image`] picks one from the response `Content-Type`.
+///
+/// The default registry holds the built-in [`ImageFormat`]s,
+/// [`register`](Self::register) adds (or replaces) one before fetching.
+///
+/// [`fetch_image`]: crate::fetcher::fetch_image
+#[derive(Clone)]
+pub struct DecoderRegistry {
+    decoders: HashMap<String, DecodeFn>,
+}
+
+impl Default for DecoderRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            decoders: HashMap::new(),
+        };
+        for format in ImageFormat::ALL {
+            registry.register(format.mime_type(), move |bytes, svg_size| {
+                format.decode(bytes, svg_size)
+            });
+        }
+        registry
+    }
+}
+
+impl DecoderRegistry {
+    /// Decode `mime_type` responses with `decode`, replacing any previous decoder for it.
+    pub fn register(
+        &mut self,
+        mime_type: &str,
+        decode: impl Fn(&[u8], Option<[u32; 2]>) -> Result<ColorImage, FetchError>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.decoders
+            .insert(mime_type.to_ascii_lowercase(), Arc::new(decode));
+    }
+
+    /// The decoder for a `Content-Type` header value, parameters like `charset` are ignored.
+    pub fn get(&self, content_type: &str) -> Option<DecodeFn> {
+        let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+        self.decoders.get(&mime_type.to_ascii_lowercase()).cloned()
+    }
+}
+
+/// Rasterize an SVG, malformed input is reported as an error rather than a panic.
+pub fn load_svg_bytes(svg_bytes: &[u8], size: Option<[u32; 2]>) -> Result<ColorImage, String> {
+    let opt = usvg::Options::default();
+    let rtree = usvg::Tree::from_data(svg_bytes, &opt.to_ref()).map_err(|err| err.to_string())?;
+
+    let fit_to = match size {
+        Some([w, h]) => usvg::FitTo::Size(w, h),
+        None => usvg::FitTo::Original,
+    };
+    let pixmap_size = fit_to
+        .fit_to(rtree.svg_node().size.to_screen_size())
+        .ok_or_else(|| "Invalid SVG size".to_owned())?;
+    let [w, h] = [pixmap_size.width(), pixmap_size.height()];
+
+    let mut pixmap = tiny_skia::Pixmap::new(w, h)
+        .ok_or_else(|| format!("Failed to create SVG Pixmap of size {}x{}", w, h))?;
+    resvg::render(&rtree, fit_to, Default::default(), pixmap.as_mut())
+        .ok_or_else(|| "Failed to render SVG".to_owned())?;
+
+    Ok(ColorImage::from_rgba_unmultiplied(
+        [w as _, h as _],
+        pixmap.data(),
+    ))
+}
+
+/// Fast non-cryptographic hash of downloaded bytes, to tell whether a re-fetch changed anything.
+///
+/// Only stable within a single build, don't persist it.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,140 @@
+use crate::{
+    fetcher::Auth,
+    http::version_label,
+    settings::Settings,
+    utils::{FetchStats, FetchTiming},
+};
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Stands in for every secret left out of the diagnostics.
+pub const REDACTED: &str = "redacted";
+
+/// How a fetch ended, as kept by [`RecentFetches`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Success,
+    Error,
+    Canceled,
+}
+
+/// A finished fetch, for the diagnostics.
+#[derive(Clone, Debug, Serialize)]
+pub struct FetchOutcome {
+    /// Credentials in it are redacted when added to [`RecentFetches`].
+    pub url: String,
+    pub outcome: Outcome,
+    pub error: Option<String>,
+    pub bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timing: Option<TimingMs>,
+}
+
+/// [`FetchTiming`] in whole milliseconds, easier to read than serialized durations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct TimingMs {
+    pub ttfb: u128,
+    pub download: u128,
+    pub decode: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<&'static str>,
+}
+
+impl From<FetchTiming> for TimingMs {
+    fn from(timing: FetchTiming) -> Self {
+        Self {
+            ttfb: timing.ttfb().as_millis(),
+            download: timing.download().as_millis(),
+            decode: timing.decode().as_millis(),
+            http_version: timing.http_version.map(version_label),
+        }
+    }
+}
+
+/// The last [`MAX_ENTRIES`](Self::MAX_ENTRIES) finished fetches, oldest first.
+#[derive(Default)]
+pub struct RecentFetches {
+    entries: VecDeque<FetchOutcome>,
+}
+
+impl RecentFetches {
+    pub const MAX_ENTRIES: usize = 20;
+
+    pub fn push(&mut self, mut outcome: FetchOutcome) {
+        outcome.url = redact_url(&outcome.url);
+        if self.entries.len() == Self::MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(outcome);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FetchOutcome> {
+        self.entries.iter()
+    }
+}
+
+/// Everything a bug report needs, serialized with [`to_json`](Self::to_json).
+///
+/// Credentials never make it in: the auth is reduced to its kind,
+/// user info in URLs (proxy included) is redacted.
+#[derive(Serialize)]
+pub struct Diagnostics<'a> {
+    pub version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub settings: Settings,
+    pub auth: &'static str,
+    pub stats: &'a FetchStats,
+    pub last_error: Option<String>,
+    pub recent_fetches: Vec<&'a FetchOutcome>,
+}
+
+impl<'a> Diagnostics<'a> {
+    pub fn new(
+        settings: &Settings,
+        auth: &Auth,
+        stats: &'a FetchStats,
+        last_error: Option<String>,
+        recent: &'a RecentFetches,
+    ) -> Self {
+        let mut settings = settings.clone();
+        settings.proxy = redact_url(&settings.proxy);
+        let auth = match auth {
+            Auth::None => "none",
+            Auth::Basic { .. } => "basic",
+            Auth::Bearer(_) => "bearer",
+        };
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            settings,
+            auth,
+            stats,
+            last_error,
+            recent_fetches: recent.iter().collect(),
+        }
+    }
+
+    /// Pretty printed, to be pasted in a bug report as is.
+    pub fn to_json(&self) -> String {
+        // Only strings, numbers and plain structs, serializing can't fail.
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// `url` with its user info (name and password) replaced by [`REDACTED`].
+///
+/// Done by hand rather than with a URL parser: proxies are often typed without a scheme,
+/// `user:pass@host:port` would parse with `user` as the scheme and keep the password.
+pub fn redact_url(url: &str) -> String {
+    let start = url.find("://").map_or(0, |scheme_end| scheme_end + 3);
+    let end = url[start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |authority_len| start + authority_len);
+    match url[start..end].rfind('@') {
+        Some(at) => format!("{}{}{}", &url[..start], REDACTED, &url[start + at..]),
+        None => url.to_owned(),
+    }
+}
@@ -0,0 +1,258 @@
+use crate::job::panic_message;
+use reqwest::StatusCode;
+use std::{fmt, time::Duration};
+use tokio::task::JoinError;
+
+/// Why a fetch (or a local load) failed.
+#[derive(Clone, Debug)]
+pub enum FetchError {
+    /// The request failed in any other way, see [`classify_error`].
+    Network(String),
+    /// The TLS handshake failed, e.g. a self-signed certificate.
+    Tls(String),
+    /// The host name couldn't be resolved.
+    Dns(String),
+    /// The connection was refused, or the host is unreachable.
+    Connect(String),
+    /// The server didn't answer within the client's timeouts.
+    NetworkTimeout(String),
+    /// The connection broke while the body was being received.
+    Body(String),
+    /// The response (or file) is not one of the supported image types.
+    UnsupportedContentType(String),
+    /// The format is known, but its decoder wasn't compiled in.
+    UnsupportedFormat {
+        mime_type: &'static str,
+        feature: &'static str,
+    },
+    /// The data was received but isn't a valid image.
+    Decode { bytes: usize, message: String },
+    /// The response is bigger than the configured limit, reading was stopped.
+    TooLarge { limit: usize },
+    /// The fetch was stopped before it finished, see [`CancelReason`].
+    Canceled { reason: CancelReason },
+    /// Offline mode is on and the image isn't cached.
+    NotCached,
+    /// The server answered `401 Unauthorized`.
+    Unauthorized,
+    /// The server answered `404 Not Found`, e.g. no image for a picsum seed.
+    NotFound,
+    /// The server answered `429 Too Many Requests`, with the `Retry-After` delay if given.
+    RateLimited { retry_after: Option<Duration> },
+    /// Any other error status, e.g. `503 Service Unavailable`.
+    Status(StatusCode),
+    /// A `data:` URI that can't be decoded, with what's wrong with it.
+    InvalidDataUri(String),
+    /// No handler is registered for the URL's scheme, see [`SchemeRegistry`].
+    ///
+    /// [`SchemeRegistry`]: crate::scheme::SchemeRegistry
+    UnsupportedScheme(String),
+    /// A bug, e.g. a decoder panicked, with the panic message.
+    Internal(String),
+    /// Anything else, e.g. a local file that can't be read.
+    Other(String),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Network(e) => write!(f, "Network error: {}", e),
+            Self::Tls(e) => write!(f, "TLS error: {}", e),
+            Self::Dns(e) => write!(f, "DNS resolution failed: {}", e),
+            Self::Connect(e) => write!(f, "Connection failed: {}", e),
+            Self::NetworkTimeout(e) => write!(f, "Timed out: {}", e),
+            Self::Body(e) => write!(f, "Download interrupted: {}", e),
+            Self::UnsupportedContentType(content_type) => write!(
+                f,
+                "Expected image/jpeg, png, webp or svg+xml, found {}",
+                content_type
+            ),
+            Self::UnsupportedFormat { mime_type, feature } => write!(
+                f,
+                "No decoder for {}, build with the `{}` feature enabled",
+                mime_type, feature
+            ),
+            Self::Decode { bytes, message } => write!(
+                f,
+                "Downloaded data is not a valid image ({} bytes received): {}",
+                bytes, message
+            ),
+            Self::TooLarge { limit } => {
+                write!(f, "Response is larger than the {} bytes limit", limit)
+            }
+            Self::Canceled {
+                reason: CancelReason::Timeout(deadline),
+            } => write!(f, "Fetching image took longer than {:?}", deadline),
+            Self::Canceled { .. } => write!(f, "Fetching image canceled."),
+            Self::NotCached => write!(f, "Not in cache (offline)"),
+            Self::Unauthorized => write!(f, "Authentication required or failed"),
+            Self::NotFound => write!(f, "No image found at this URL"),
+            Self::RateLimited {
+                retry_after: Some(delay),
+            } => write!(f, "Rate limited, retry in {}s.", delay.as_secs()),
+            Self::RateLimited { retry_after: None } => write!(f, "Rate limited by the server"),
+            Self::Status(status) => write!(f, "The server answered {}", status),
+            Self::InvalidDataUri(e) => write!(f, "Invalid data URI: {}", e),
+            Self::UnsupportedScheme(scheme) => write!(f, "No handler for {}: URLs", scheme),
+            Self::Internal(e) => write!(f, "Internal error, please report it: {}", e),
+            Self::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl FetchError {
+    /// Whether trying again may succeed, e.g. a dropped connection.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Network(_)
+                | Self::Connect(_)
+                | Self::NetworkTimeout(_)
+                | Self::Body(_)
+                | Self::Canceled {
+                    reason: CancelReason::Timeout(_)
+                }
+                | Self::RateLimited { .. }
+        )
+    }
+
+    /// Whether the fetch was stopped on purpose, there's no error to show then.
+    pub fn is_deliberate_cancel(&self) -> bool {
+        match self {
+            Self::Canceled { reason } => reason.is_deliberate(),
+            _ => false,
+        }
+    }
+
+    /// Whether a mirror may have what this host failed to serve, see
+    /// [`AsyncFetcher::start_with_mirrors`](crate::AsyncFetcher::start_with_mirrors).
+    ///
+    /// Network errors and error statuses qualify, 4xx ones included.
+    pub fn fails_over(&self) -> bool {
+        matches!(
+            self,
+            Self::Network(_)
+                | Self::Tls(_)
+                | Self::Dns(_)
+                | Self::Connect(_)
+                | Self::NetworkTimeout(_)
+                | Self::Body(_)
+                | Self::Unauthorized
+                | Self::NotFound
+                | Self::RateLimited { .. }
+                | Self::Status(_)
+        )
+    }
+
+    /// What the user can do about a network error, shown under its message.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::Network(_) => Some("Check your internet connection."),
+            Self::Tls(_) => Some(
+                "The host's certificate is invalid. For self-signed certificates, \
+                 enable \"Allow invalid certificates\" in the settings.",
+            ),
+            Self::Dns(_) => Some("Check the host name and your internet connection."),
+            Self::Connect(_) => {
+                Some("The host can't be reached, check the address, the port and the proxy.")
+            }
+            Self::NetworkTimeout(_) => Some("The host is slow to answer, try again later."),
+            Self::Body(_) => Some("The connection dropped during the download, try again."),
+            _ => None,
+        }
+    }
+
+    /// How long the server asked to wait before trying again.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+/// Why a fetch was stopped before it finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CancelReason {
+    /// The user canceled it, e.g. with the cancel button.
+    UserRequested,
+    /// Download plus decode took longer than the configured deadline.
+    Timeout(Duration),
+    /// The fetcher was dropped, e.g. the app is closing.
+    Shutdown,
+    /// A newer fetch replaced it, its result is of no use anymore.
+    Superseded,
+}
+
+impl CancelReason {
+    /// Everything but timeouts, which are failures.
+    pub fn is_deliberate(self) -> bool {
+        !matches!(self, Self::Timeout(_))
+    }
+}
+
+impl From<CancelReason> for FetchError {
+    fn from(reason: CancelReason) -> Self {
+        Self::Canceled { reason }
+    }
+}
+
+// A blocking step (decoding...) didn't finish.
+impl From<JoinError> for FetchError {
+    fn from(e: JoinError) -> Self {
+        if e.is_panic() {
+            Self::Internal(panic_message(e.into_panic()))
+        } else {
+            Self::Other(e.to_string())
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        classify_error(&e).0
+    }
+}
+
+/// Sort a request error into DNS, TLS, connection, timeout or body failures,
+/// along with the hint to show the user, see [`FetchError::hint`].
+pub fn classify_error(e: &reqwest::Error) -> (FetchError, &'static str) {
+    let error = classify(e);
+    let hint = error.hint().unwrap_or("Check your internet connection.");
+    (error, hint)
+}
+
+fn classify(e: &reqwest::Error) -> FetchError {
+    // reqwest only says "error trying to connect", the TLS backend tells more.
+    let mut source = std::error::Error::source(e);
+    while let Some(cause) = source {
+        let message = cause.to_string();
+        let lowercase = message.to_ascii_lowercase();
+        if lowercase.contains("certificate") {
+            return FetchError::Tls(message);
+        }
+        // From the resolver, e.g. "failed to lookup address information".
+        if lowercase.contains("dns error") || lowercase.contains("lookup address") {
+            return FetchError::Dns(message);
+        }
+        source = cause.source();
+    }
+    // The innermost cause, e.g. "Connection refused (os error 111)".
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(e);
+    while let Some(cause) = source {
+        message = cause.to_string();
+        source = cause.source();
+    }
+    if e.is_timeout() {
+        FetchError::NetworkTimeout(message)
+    } else if e.is_connect() {
+        FetchError::Connect(message)
+    } else if e.is_body() || e.is_decode() {
+        FetchError::Body(message)
+    } else {
+        FetchError::Network(e.to_string())
+    }
+}
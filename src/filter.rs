@@ -0,0 +1,49 @@
+use eframe::egui::{Color32, ColorImage};
+use image::RgbaImage;
+
+/// Post-processing filters applied to the displayed image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFilter {
+    Grayscale,
+    Invert,
+}
+
+impl ImageFilter {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Grayscale => "Grayscale",
+            Self::Invert => "Invert",
+        }
+    }
+
+    /// Produce a filtered copy of `image`, this is blocking work.
+    pub fn apply(self, image: &ColorImage) -> ColorImage {
+        let mut rgba = to_rgba_image(image);
+        match self {
+            Self::Grayscale => {
+                // Convert back to RGBA so the alpha channel survives.
+                let gray = image::DynamicImage::ImageRgba8(rgba).grayscale();
+                rgba = gray.to_rgba8();
+            }
+            Self::Invert => image::imageops::invert(&mut rgba),
+        }
+        from_rgba_image(&rgba)
+    }
+}
+
+/// Convert egui pixels into an `image` buffer.
+pub fn to_rgba_image(image: &ColorImage) -> RgbaImage {
+    let bytes = image
+        .pixels
+        .iter()
+        .flat_map(Color32::to_srgba_unmultiplied)
+        .collect();
+    RgbaImage::from_raw(image.width() as u32, image.height() as u32, bytes)
+        .expect("pixel count matches image size")
+}
+
+/// Convert an `image` buffer back into egui pixels.
+pub fn from_rgba_image(image: &RgbaImage) -> ColorImage {
+    let size = [image.width() as usize, image.height() as usize];
+    ColorImage::from_rgba_unmultiplied(size, image.as_raw())
+}
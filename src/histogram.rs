@@ -0,0 +1,40 @@
+use eframe::egui::{Color32, ColorImage};
+
+/// Weights of red, green and blue in the luma, out of 10000 (Rec. 709 like `image`'s grayscale).
+const LUMA_WEIGHTS: [u32; 3] = [2126, 7152, 722];
+
+/// How many pixels of an image have each value, per channel, see [`Histogram::of`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Histogram {
+    pub red: [u32; 256],
+    pub green: [u32; 256],
+    pub blue: [u32; 256],
+    pub luma: [u32; 256],
+}
+
+impl Histogram {
+    /// Count the values of every pixel of `image`, this is blocking work for large images.
+    ///
+    /// Values are unmultiplied, a translucent pixel counts like an opaque one of its color.
+    pub fn of(image: &ColorImage) -> Self {
+        let mut histogram = Self {
+            red: [0; 256],
+            green: [0; 256],
+            blue: [0; 256],
+            luma: [0; 256],
+        };
+        for [r, g, b, _] in image.pixels.iter().map(Color32::to_srgba_unmultiplied) {
+            histogram.red[r as usize] += 1;
+            histogram.green[g as usize] += 1;
+            histogram.blue[b as usize] += 1;
+            histogram.luma[luma(r, g, b) as usize] += 1;
+        }
+        histogram
+    }
+}
+
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+    let [wr, wg, wb] = LUMA_WEIGHTS;
+    let sum = wr * r as u32 + wg * g as u32 + wb * b as u32;
+    ((sum + 5000) / 10000) as u8
+}
@@ -0,0 +1,125 @@
+use crate::FetchError;
+use async_trait::async_trait;
+use reqwest::{header::HeaderMap, Client, Response, StatusCode, Version};
+
+/// The HTTP layer [`fetch_image`](crate::fetcher::fetch_image) goes through.
+///
+/// Implemented for [`reqwest::Client`], the default. Another backend (or an in-memory
+/// fake in tests) only has to send a GET and stream the body back.
+#[async_trait]
+pub trait HttpClient: Send + Sync {
+    /// Send a GET to `url` with `headers`, the body is read from the returned response.
+    ///
+    /// Redirects are the backend's business, timeouts too.
+    async fn get_stream(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+    ) -> Result<Box<dyn HttpResponse>, FetchError>;
+}
+
+/// A response whose body is read chunk by chunk, see [`HttpClient`].
+#[async_trait]
+pub trait HttpResponse: Send + Sync {
+    fn status(&self) -> StatusCode;
+
+    fn headers(&self) -> &HeaderMap;
+
+    /// Where the response came from, after redirects.
+    fn url(&self) -> &str;
+
+    /// The `Content-Length` of the body, when the server told it.
+    fn content_length(&self) -> Option<u64>;
+
+    /// The HTTP version negotiated for the response, when the backend tells.
+    fn version(&self) -> Option<Version> {
+        None
+    }
+
+    /// The next chunk of the body, `None` once it's all been read.
+    async fn chunk(&mut self) -> Result<Option<Vec<u8>>, FetchError>;
+}
+
+#[async_trait]
+impl HttpClient for Client {
+    async fn get_stream(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+    ) -> Result<Box<dyn HttpResponse>, FetchError> {
+        let response = self.get(url).headers(headers).send().await?;
+        Ok(Box::new(response))
+    }
+}
+
+#[async_trait]
+impl HttpResponse for Response {
+    fn status(&self) -> StatusCode {
+        Response::status(self)
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        Response::headers(self)
+    }
+
+    fn url(&self) -> &str {
+        Response::url(self).as_str()
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        Response::content_length(self)
+    }
+
+    fn version(&self) -> Option<Version> {
+        Some(Response::version(self))
+    }
+
+    async fn chunk(&mut self) -> Result<Option<Vec<u8>>, FetchError> {
+        let chunk = Response::chunk(self).await?;
+        Ok(chunk.map(|bytes| bytes.to_vec()))
+    }
+}
+
+/// Headers that may carry credentials or session tokens.
+pub const SENSITIVE_HEADERS: [&str; 5] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "www-authenticate",
+];
+
+/// Stands for the value of a redacted header.
+pub const REDACTED: &str = "<redacted>";
+
+/// `headers` as (name, value) pairs to show, in the order received.
+///
+/// With `redact` the values of [`SENSITIVE_HEADERS`] are replaced with [`REDACTED`],
+/// values marked sensitive always are. Values that aren't text are shown lossily.
+pub fn header_pairs(headers: &HeaderMap, redact: bool) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let hidden =
+                value.is_sensitive() || (redact && SENSITIVE_HEADERS.contains(&name.as_str()));
+            let value = if hidden {
+                REDACTED.to_owned()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.as_str().to_owned(), value)
+        })
+        .collect()
+}
+
+/// How `version` is usually written, e.g. `HTTP/2`.
+pub fn version_label(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_11 => "HTTP/1.1",
+        Version::HTTP_2 => "HTTP/2",
+        Version::HTTP_3 => "HTTP/3",
+        _ => "HTTP",
+    }
+}
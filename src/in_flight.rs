@@ -0,0 +1,237 @@
+use crate::{
+    priority::Priority,
+    utils::{Channel, Container, FetchTiming},
+    FetchError,
+};
+use reqwest::header::HeaderMap;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::watch;
+
+/// The leader's result, after the progress messages describing it.
+pub type SharedResult = (Vec<Channel>, Result<Container, FetchError>);
+
+// `None` until the leader is done.
+type Shared = Option<(Told, Result<Container, FetchError>)>;
+
+// Which fetches give the same result: the URL, and the hash told apart as unchanged.
+type Key = (String, Option<u64>);
+
+// The leader of a URL, as seen by the fetches joining it.
+struct Entry {
+    id: u64,
+    priority: Priority,
+    // Past the limiter, see `Leader::set_running`.
+    running: Arc<AtomicBool>,
+    result: watch::Receiver<Shared>,
+}
+
+impl Entry {
+    // A fetch for `priority` wouldn't wait longer following this one than on its own.
+    fn can_lead(&self, priority: Priority) -> bool {
+        self.priority == Priority::User
+            || priority == Priority::Background
+            || self.running.load(Ordering::Acquire)
+    }
+}
+
+/// Image fetches in progress by URL, shared by every fetch of an [`AsyncFetcher`].
+///
+/// The first fetch of a URL [leads](Joined::Leader) and downloads it, fetches of the same URL
+/// started meanwhile [follow](Joined::Follower) it and get a copy of its result instead
+/// of downloading it again. A leader that doesn't get a result to share (canceled, dropped
+/// on shutdown...) lets its followers fetch on their own.
+///
+/// A user fetch doesn't follow a background one still waiting for the limiter, it would
+/// wait behind the other user fetches with it. It leads a fetch of its own instead.
+///
+/// [`AsyncFetcher`]: crate::AsyncFetcher
+#[derive(Default)]
+pub struct InFlight {
+    fetches: Mutex<HashMap<Key, Entry>>,
+    next_id: AtomicU64,
+}
+
+/// What joining an [`InFlight`] fetch of a URL gives.
+pub enum Joined {
+    /// No fetch of the URL is in progress, this one has to download it.
+    Leader(Box<Leader>),
+    /// Another fetch of the URL is in progress, wait for its result.
+    Follower(Follower),
+}
+
+impl InFlight {
+    /// Lead the fetch of `url` for `priority`, or follow the one in progress.
+    ///
+    /// `known_hash` is part of the key, see [`AsyncFetcher::start_if_changed`].
+    ///
+    /// [`AsyncFetcher::start_if_changed`]: crate::AsyncFetcher::start_if_changed
+    pub fn join(
+        self: &Arc<Self>,
+        url: &str,
+        known_hash: Option<u64>,
+        priority: Priority,
+    ) -> Joined {
+        let key = (url.to_owned(), known_hash);
+        let mut fetches = self.fetches.lock().unwrap();
+        if let Some(entry) = fetches.get(&key).filter(|entry| entry.can_lead(priority)) {
+            return Joined::Follower(Follower(entry.result.clone()));
+        }
+        // Replaces a background leader still waiting, its followers stay with it.
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let running = Arc::new(AtomicBool::new(false));
+        let (sender, result) = watch::channel(None);
+        let entry = Entry {
+            id,
+            priority,
+            running: running.clone(),
+            result,
+        };
+        fetches.insert(key.clone(), entry);
+        Joined::Leader(Box::new(Leader {
+            in_flight: self.clone(),
+            key,
+            id,
+            running,
+            sender,
+            told: Default::default(),
+        }))
+    }
+
+    /// How many URLs are being fetched.
+    pub fn len(&self) -> usize {
+        self.fetches.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The fetch of a URL others may follow, its URL is free again once dropped.
+pub struct Leader {
+    in_flight: Arc<InFlight>,
+    key: Key,
+    id: u64,
+    running: Arc<AtomicBool>,
+    sender: watch::Sender<Shared>,
+    told: Mutex<Told>,
+}
+
+// What the leader's UI was told about its download, followers are told the same.
+#[derive(Default, Clone)]
+struct Told {
+    total: Option<usize>,
+    bytes: usize,
+    headers: Option<HeaderMap>,
+    cached: bool,
+    timing: Option<FetchTiming>,
+}
+
+impl Told {
+    fn messages(&self) -> Vec<Channel> {
+        let mut messages = Vec::new();
+        messages.extend(self.total.map(Channel::ImageTotal));
+        messages.extend(self.headers.clone().map(Channel::ImageHeaders));
+        if self.bytes > 0 {
+            messages.push(Channel::Image(self.bytes));
+        }
+        if self.cached {
+            messages.push(Channel::ImageCached);
+        }
+        messages.extend(self.timing.map(Channel::ImageTiming));
+        messages
+    }
+}
+
+impl Leader {
+    /// The limiter let this fetch through, user fetches may follow it from now on.
+    pub fn set_running(&self) {
+        self.running.store(true, Ordering::Release);
+    }
+
+    /// Keep what followers need of `message` to describe the result: size, headers,
+    /// source and timing. The rest only matters while the download runs.
+    pub fn record(&self, message: &Channel) {
+        let mut told = self.told.lock().unwrap();
+        match message {
+            Channel::ImageTotal(total) => told.total = Some(*total),
+            Channel::Image(bytes) => told.bytes += bytes,
+            Channel::ImageHeaders(headers) => told.headers = Some(headers.clone()),
+            Channel::ImageCached => told.cached = true,
+            Channel::ImageTiming(timing) => told.timing = Some(*timing),
+            // Starting over on a mirror, nothing of the failed URL applies.
+            Channel::Mirror(_) => *told = Told::default(),
+            _ => {}
+        }
+    }
+
+    /// Hand a copy of `result` to the followers, after what was [recorded](Self::record).
+    ///
+    /// Cancelations and raw data aren't shared, followers fetch on their own then.
+    pub fn finish(self, result: &Result<Container, FetchError>) {
+        if let Some(result) = share(result) {
+            let told = self.told.lock().unwrap().clone();
+            self.sender.send_replace(Some((told, result)));
+        }
+    }
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        let mut fetches = self.in_flight.fetches.lock().unwrap();
+        // Unless a user fetch took the URL over meanwhile.
+        if fetches
+            .get(&self.key)
+            .map_or(false, |entry| entry.id == self.id)
+        {
+            fetches.remove(&self.key);
+        }
+    }
+}
+
+/// Waits for the result of the fetch it follows.
+pub struct Follower(watch::Receiver<Shared>);
+
+impl Follower {
+    /// The leader's result, `None` when it had none to share.
+    ///
+    /// Cancel safe, it can be raced against the follower's own cancelation.
+    pub async fn result(&mut self) -> Option<SharedResult> {
+        // An error is only returned once the leader is gone, with or without a result.
+        loop {
+            if self.0.borrow().is_some() {
+                break;
+            }
+            if self.0.changed().await.is_err() {
+                break;
+            }
+        }
+        let shared = self.0.borrow();
+        let (told, result) = shared.as_ref()?;
+        Some((told.messages(), share(result)?))
+    }
+}
+
+// A copy of `result` for a follower. Textures are reference counted, cheap to copy.
+fn share(result: &Result<Container, FetchError>) -> Option<Result<Container, FetchError>> {
+    match result {
+        Ok(Container::Image(image, pixels, hash)) => {
+            Some(Ok(Container::Image(image.clone(), pixels.clone(), *hash)))
+        }
+        Ok(Container::Animation(animation, pixels, hash)) => Some(Ok(Container::Animation(
+            animation.clone(),
+            pixels.clone(),
+            *hash,
+        ))),
+        Ok(Container::Unchanged) => Some(Ok(Container::Unchanged)),
+        Ok(Container::Data(_) | Container::File(_)) => None,
+        Err(FetchError::Canceled { reason }) if reason.is_deliberate() => None,
+        Err(e) => Some(Err(e.clone())),
+    }
+}
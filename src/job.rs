@@ -0,0 +1,94 @@
+use crate::AsyncFetcher;
+use flowync::{error::Compact, CompactFlower};
+use std::{
+    any::Any,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A one-shot blocking computation (filtering, encoding, ...) run off the UI thread.
+///
+/// Like fetches, the result comes back through a flower, polled once per frame.
+pub struct BlockingJob<T: Send> {
+    flower: CompactFlower<(), T, String>,
+}
+
+impl<T: Send + 'static> BlockingJob<T> {
+    pub fn new() -> Self {
+        Self {
+            flower: CompactFlower::new(1),
+        }
+    }
+
+    /// Run `f` with `spawn_blocking` on the fetcher's runtime.
+    pub fn spawn(&self, fetcher: &AsyncFetcher, f: impl FnOnce() -> T + Send + 'static) {
+        let handle = self.flower.handle();
+        handle.activate();
+        fetcher.runtime_handle().spawn(async move {
+            match tokio::task::spawn_blocking(f).await {
+                Ok(value) => handle.success(value),
+                Err(e) => handle.error(e.to_string()),
+            }
+        });
+    }
+
+    /// Check if the job is still running.
+    pub fn is_active(&self) -> bool {
+        self.flower.is_active()
+    }
+
+    /// Take the result once the job is done, should be called once per frame.
+    pub fn poll(&self) -> Option<Result<T, String>> {
+        let mut result = None;
+        self.flower.try_result(|r| {
+            result = Some(r.map_err(|e| match e {
+                Compact::Suppose(e) | Compact::Panicked(e) => e,
+            }))
+        });
+        result
+    }
+}
+
+impl<T: Send + 'static> Default for BlockingJob<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Await `future`, a panic while polling it comes back as an `Err` with the panic message.
+///
+/// Tokio catches the panic of a spawned task, but only drops the task afterwards:
+/// a flower handle owned by the task is then never finalized and the UI waits forever.
+/// Wrapping the task's work lets it report the panic like any other error.
+pub async fn catch_panic<F: Future>(future: F) -> Result<F::Output, String> {
+    CatchPanic(Box::pin(future)).await
+}
+
+struct CatchPanic<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchPanic<F> {
+    type Output = Result<F::Output, String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let future = self.0.as_mut();
+        // Never polled again after a panic, whatever state it was left in doesn't matter.
+        match panic::catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(panic_message(payload))),
+        }
+    }
+}
+
+/// The message a panic was raised with, e.g. from [`tokio::task::JoinError::into_panic`].
+pub fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => "unknown panic".into(),
+        },
+    }
+}
@@ -0,0 +1,33 @@
+//! Eframe + Tokio async integration plumbing.
+//!
+//! [`AsyncFetcher`] owns a tokio runtime and a [`flowync`] flower, so an immediate mode UI
+//! can start a fetch, poll it once per frame and cancel it without ever blocking the UI thread.
+pub mod animation;
+pub mod archive;
+pub mod batch;
+pub mod cache;
+pub mod clipboard;
+pub mod data_uri;
+pub mod decode;
+pub mod diagnostics;
+pub mod error;
+pub mod fetcher;
+pub mod filter;
+pub mod histogram;
+pub mod http;
+pub mod in_flight;
+pub mod job;
+pub mod priority;
+pub mod progress;
+pub mod provider;
+pub mod rate_limit;
+pub mod scheme;
+pub mod settings;
+pub mod texture;
+pub mod thumbnail;
+pub mod toast;
+pub mod utils;
+pub mod viewer;
+
+pub use error::{CancelReason, FetchError};
+pub use fetcher::{AsyncFetcher, FetchConfig, FetchId, FetchKind, FetchState, Polled};
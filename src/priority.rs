@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use tokio::sync::{watch, AcquireError, OwnedSemaphorePermit, Semaphore};
+
+/// Who a fetch is for, see [`PriorityGate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Priority {
+    /// Asked for by the user, e.g. prev/next or a typed URL.
+    User,
+    /// Work the user isn't waiting on, e.g. history thumbnails and batches.
+    Background,
+}
+
+/// Lets user fetches go before background ones waiting for the same limiter.
+///
+/// The limiter's queue is first come first served: background fetches wait while a user
+/// fetch does, and give back a permit they got meanwhile. Running fetches are left alone.
+pub struct PriorityGate {
+    // User fetches waiting for a permit.
+    user_waiting: watch::Sender<usize>,
+}
+
+// Counts a waiting user fetch, until it gets its permit or is dropped.
+struct UserWaiting<'a>(&'a watch::Sender<usize>);
+
+impl Drop for UserWaiting<'_> {
+    fn drop(&mut self) {
+        self.0.send_modify(|waiting| *waiting -= 1);
+    }
+}
+
+impl PriorityGate {
+    pub fn new() -> Self {
+        Self {
+            user_waiting: watch::channel(0).0,
+        }
+    }
+
+    /// Wait for a permit of `limiter`, behind every waiting user fetch for a background one.
+    pub async fn acquire(
+        &self,
+        limiter: Arc<Semaphore>,
+        priority: Priority,
+    ) -> Result<OwnedSemaphorePermit, AcquireError> {
+        if priority == Priority::User {
+            self.user_waiting.send_modify(|waiting| *waiting += 1);
+            let _waiting = UserWaiting(&self.user_waiting);
+            return limiter.acquire_owned().await;
+        }
+        let mut user_waiting = self.user_waiting.subscribe();
+        loop {
+            // The sender lives as long as `self`, this can't fail.
+            let _ = user_waiting.wait_for(|waiting| *waiting == 0).await;
+            let permit = limiter.clone().acquire_owned().await?;
+            if *self.user_waiting.borrow() == 0 {
+                return Ok(permit);
+            }
+            // A user fetch queued up behind this one, its turn first.
+            drop(permit);
+        }
+    }
+
+    /// User fetches waiting for a permit right now.
+    pub fn user_waiting(&self) -> usize {
+        *self.user_waiting.borrow()
+    }
+}
+
+impl Default for PriorityGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    fetcher::TypedFlowerHandle,
+    in_flight::Leader,
+    texture::TextureImage,
+    utils::{Channel, Compression, FetchTiming},
+};
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use std::{collections::VecDeque, sync::Mutex};
+
+/// Receives download progress from [`fetch_image`](crate::fetcher::fetch_image).
+///
+/// [`QueuedProgress`] is the one [`AsyncFetcher`](crate::AsyncFetcher) uses,
+/// embedders can plug in their own (a CLI progress bar, a log...) and call `fetch_image` directly.
+#[async_trait]
+pub trait ProgressSink: Send + Sync {
+    /// A chunk of `chunk_len` bytes was received.
+    async fn on_bytes(&self, chunk_len: usize);
+    /// The server announced the body size.
+    async fn on_total(&self, total: usize);
+    /// The response came, with these headers. Sent for `304 Not Modified` too.
+    async fn on_headers(&self, _headers: &HeaderMap) {}
+    /// No chunk arrived within the stall timeout.
+    async fn on_stalled(&self) {}
+    /// The partial download could be decoded, see [`FetchConfig::progressive_preview`].
+    ///
+    /// [`FetchConfig::progressive_preview`]: crate::FetchConfig::progressive_preview
+    async fn on_preview(&self, _image: TextureImage) {}
+    /// The download is complete, decoding starts.
+    async fn on_decoding(&self) {}
+    /// The image is decoded, sent whether or not the fetch is canceled afterwards.
+    async fn on_decoded(&self) {}
+    /// The image is decoded, with the time each step took.
+    async fn on_timing(&self, _timing: FetchTiming) {}
+    /// The image is served from the cache, offline or not modified since it was cached.
+    async fn on_cached(&self) {}
+    /// The raw data came compressed and was decompressed, see [`FetchConfig::compression`].
+    ///
+    /// [`FetchConfig::compression`]: crate::FetchConfig::compression
+    async fn on_decompressed(&self, _compression: Compression) {}
+    /// Checked between chunks, returning `true` stops the fetch.
+    fn should_cancel(&self) -> bool {
+        false
+    }
+}
+
+/// Progress messages waiting for the UI, between a fetch task and [`AsyncFetcher::poll`].
+///
+/// Pushing never waits, so a fast download isn't held back by the frame rate, and the queue
+/// holds at most `capacity` messages. When it's full, room is made by summing up
+/// the oldest adjacent byte counts, then by dropping the oldest stall notice or preview,
+/// then the oldest message. Results don't go through here, they're never dropped.
+///
+/// [`AsyncFetcher::poll`]: crate::AsyncFetcher::poll
+pub struct ProgressQueue {
+    messages: Mutex<VecDeque<Channel>>,
+    capacity: usize,
+}
+
+impl ProgressQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            messages: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn push(&self, message: Channel) {
+        let mut messages = self.messages.lock().unwrap();
+        if messages.len() >= self.capacity {
+            make_room(&mut messages);
+        }
+        messages.push_back(message);
+    }
+
+    /// The oldest message, if any.
+    pub fn pop(&self) -> Option<Channel> {
+        self.messages.lock().unwrap().pop_front()
+    }
+
+    /// Every message, oldest first, leaving the queue empty.
+    pub fn drain(&self) -> Vec<Channel> {
+        self.messages.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Free one slot of a full queue, losing as little as possible.
+fn make_room(messages: &mut VecDeque<Channel>) {
+    // Byte counts summed up keep the total right, only the steps are coarser.
+    let merged = (1..messages.len()).find_map(|i| match (&messages[i - 1], &messages[i]) {
+        (Channel::Image(a), Channel::Image(b)) => Some((i, Channel::Image(a + b))),
+        (Channel::Data(a), Channel::Data(b)) => Some((i, Channel::Data(a + b))),
+        _ => None,
+    });
+    if let Some((i, sum)) = merged {
+        messages[i - 1] = sum;
+        messages.remove(i);
+        return;
+    }
+    // Both only matter until the next message.
+    let stale = messages
+        .iter()
+        .position(|message| matches!(message, Channel::ImageStalled | Channel::ImagePreview(_)));
+    match stale {
+        Some(i) => {
+            messages.remove(i);
+        }
+        None => {
+            messages.pop_front();
+        }
+    }
+}
+
+/// The sink of the fetches run by [`AsyncFetcher`](crate::AsyncFetcher): progress goes to
+/// the queue [`poll`](crate::AsyncFetcher::poll) drains, cancelation is read from the flower.
+///
+/// Fetches others follow also [record](Leader::record) it for them.
+#[derive(Clone, Copy)]
+pub struct QueuedProgress<'a> {
+    pub handle: &'a TypedFlowerHandle,
+    pub queue: &'a ProgressQueue,
+    pub leader: Option<&'a Leader>,
+}
+
+impl QueuedProgress<'_> {
+    pub fn send(&self, message: Channel) {
+        if let Some(leader) = self.leader {
+            leader.record(&message);
+        }
+        self.queue.push(message);
+    }
+}
+
+#[async_trait]
+impl ProgressSink for QueuedProgress<'_> {
+    async fn on_bytes(&self, chunk_len: usize) {
+        self.send(Channel::Image(chunk_len));
+    }
+
+    async fn on_total(&self, total: usize) {
+        self.send(Channel::ImageTotal(total));
+    }
+
+    async fn on_headers(&self, headers: &HeaderMap) {
+        self.send(Channel::ImageHeaders(headers.clone()));
+    }
+
+    async fn on_stalled(&self) {
+        self.send(Channel::ImageStalled);
+    }
+
+    async fn on_preview(&self, image: TextureImage) {
+        self.send(Channel::ImagePreview(image));
+    }
+
+    async fn on_decoding(&self) {
+        self.send(Channel::ImageDecoding);
+    }
+
+    async fn on_decoded(&self) {
+        self.send(Channel::ImageDecoded);
+    }
+
+    async fn on_timing(&self, timing: FetchTiming) {
+        self.send(Channel::ImageTiming(timing));
+    }
+
+    async fn on_cached(&self) {
+        self.send(Channel::ImageCached);
+    }
+
+    fn should_cancel(&self) -> bool {
+        self.handle.should_cancel()
+    }
+}
+
+/// Reports like [`QueuedProgress`], but as [`Channel::Data`] progress.
+pub struct DataProgress<'a>(pub QueuedProgress<'a>);
+
+#[async_trait]
+impl ProgressSink for DataProgress<'_> {
+    async fn on_bytes(&self, chunk_len: usize) {
+        self.0.send(Channel::Data(chunk_len));
+    }
+
+    async fn on_total(&self, _total: usize) {}
+
+    async fn on_decompressed(&self, compression: Compression) {
+        self.0.send(Channel::DataCompressed(compression));
+    }
+
+    fn should_cancel(&self) -> bool {
+        self.0.should_cancel()
+    }
+}
@@ -0,0 +1,48 @@
+/// Builds image URLs from a seed, so prev/next browsing works with any seed-based host.
+pub trait ImageProvider {
+    /// Shown in the provider picker.
+    fn name(&self) -> &str;
+    /// URL of the `size`x`size` image for `seed`.
+    fn url_for(&self, seed: usize, size: usize) -> String;
+}
+
+/// <https://picsum.photos>, the default provider.
+pub struct PicsumProvider;
+
+impl ImageProvider for PicsumProvider {
+    fn name(&self) -> &str {
+        "Picsum"
+    }
+
+    fn url_for(&self, seed: usize, size: usize) -> String {
+        format!("https://picsum.photos/seed/{}/{}", seed, size)
+    }
+}
+
+/// A local test server using the same `/seed/<seed>/<size>` scheme as picsum.
+pub struct LocalProvider {
+    pub base_url: String,
+}
+
+impl Default for LocalProvider {
+    fn default() -> Self {
+        Self {
+            base_url: "http://127.0.0.1:8000".into(),
+        }
+    }
+}
+
+impl ImageProvider for LocalProvider {
+    fn name(&self) -> &str {
+        "Local test server"
+    }
+
+    fn url_for(&self, seed: usize, size: usize) -> String {
+        format!(
+            "{}/seed/{}/{}",
+            self.base_url.trim_end_matches('/'),
+            seed,
+            size
+        )
+    }
+}
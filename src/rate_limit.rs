@@ -0,0 +1,45 @@
+use reqwest::Url;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Spaces out requests to the same host, shared by every fetch of an [`AsyncFetcher`].
+///
+/// A token bucket holding a single token per host: a request takes it, the next one
+/// waits until it's back, one interval later. Quick prev/next browsing then doesn't
+/// trip provider rate limits.
+///
+/// [`AsyncFetcher`]: crate::AsyncFetcher
+#[derive(Default)]
+pub struct HostRateLimiter {
+    // When each host's token is back.
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostRateLimiter {
+    /// Take the next slot of `url`'s host at `per_sec` requests per second,
+    /// returns how long to wait for it.
+    ///
+    /// The slot is taken right away, whether or not the caller ends up waiting for it.
+    /// URLs without a host and non positive rates never wait.
+    pub fn reserve(&self, url: &str, per_sec: f64) -> Duration {
+        let host = match Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_owned))
+        {
+            Some(host) => host,
+            None => return Duration::ZERO,
+        };
+        if per_sec <= 0.0 || !per_sec.is_finite() {
+            return Duration::ZERO;
+        }
+        let interval = Duration::from_secs_f64(1.0 / per_sec);
+        let now = Instant::now();
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let slot = next_slot.get(&host).map_or(now, |next| (*next).max(now));
+        next_slot.insert(host, slot + interval);
+        slot - now
+    }
+}
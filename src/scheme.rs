@@ -0,0 +1,76 @@
+use crate::{data_uri::DataUri, FetchError};
+use std::{collections::HashMap, sync::Arc};
+
+/// Produces the bytes behind a URL of a custom scheme. Runs with `spawn_blocking`,
+/// it may read files or do other blocking work.
+pub type SchemeFn = Arc<dyn Fn(&str) -> Result<Vec<u8>, FetchError> + Send + Sync>;
+
+/// Handlers by URL scheme, [`fetch_with_scheme`] gets the image bytes through them.
+///
+/// `http` and `https` always go through the HTTP client. The default registry
+/// also reads `file://` URLs and decodes `data:` URIs, [`register`](Self::register)
+/// adds (or replaces) a scheme before fetching. Any other scheme is an error.
+///
+/// [`fetch_with_scheme`]: crate::fetcher::fetch_with_scheme
+#[derive(Clone)]
+pub struct SchemeRegistry {
+    handlers: HashMap<String, SchemeFn>,
+}
+
+impl Default for SchemeRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            handlers: HashMap::new(),
+        };
+        registry.register("file", read_file_url);
+        registry.register("data", |url| DataUri::parse(url).map(|uri| uri.bytes));
+        registry
+    }
+}
+
+impl SchemeRegistry {
+    /// Fetch `scheme` URLs with `handler`, replacing any previous handler for it.
+    pub fn register(
+        &mut self,
+        scheme: &str,
+        handler: impl Fn(&str) -> Result<Vec<u8>, FetchError> + Send + Sync + 'static,
+    ) {
+        self.handlers
+            .insert(scheme.to_ascii_lowercase(), Arc::new(handler));
+    }
+
+    /// The handler for the scheme of `url`, `None` when it goes through the HTTP client.
+    ///
+    /// URLs without a scheme are left to the client too, it tells what's wrong with them.
+    pub fn handler_for(&self, url: &str) -> Result<Option<SchemeFn>, FetchError> {
+        let scheme = match scheme_of(url) {
+            Some(scheme) => scheme.to_ascii_lowercase(),
+            None => return Ok(None),
+        };
+        if scheme == "http" || scheme == "https" {
+            return Ok(None);
+        }
+        self.handlers
+            .get(&scheme)
+            .cloned()
+            .map(Some)
+            .ok_or(FetchError::UnsupportedScheme(scheme))
+    }
+}
+
+/// The scheme of `url`, as written, e.g. `https` or `data`.
+pub fn scheme_of(url: &str) -> Option<&str> {
+    let (scheme, _) = url.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then(|| scheme)
+}
+
+fn read_file_url(url: &str) -> Result<Vec<u8>, FetchError> {
+    let path = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .ok_or_else(|| FetchError::Other(format!("{} isn't a valid file URL", url)))?;
+    std::fs::read(&path).map_err(|e| crate::fetcher::read_error(&path, e))
+}
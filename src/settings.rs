@@ -0,0 +1,206 @@
+use crate::{
+    fetcher::{FetchConfig, DEFAULT_MAX_CONCURRENT},
+    texture::ResizeFilter,
+};
+use eframe::Storage;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path, time::Duration};
+
+/// Every user facing option of the app, persisted as a whole with [`eframe::Storage`].
+///
+/// Credentials are left out on purpose, they're never persisted.
+/// Fields missing from an older save take their default value.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub max_concurrent: usize,
+    pub max_image_mb: usize,
+    pub deadline_secs: u64,
+    pub offline: bool,
+    pub prefer_webp: bool,
+    pub progressive_preview: bool,
+    /// Request images sized to the window instead of a fixed size.
+    pub adaptive_image_size: bool,
+    pub auto_retry: bool,
+    pub auto_retry_attempts: usize,
+    /// Index of the selected image provider.
+    pub provider: usize,
+    /// Base URLs of hosts with the same `/seed/<seed>/<size>` images, one per line.
+    /// Tried in order when the provider fails.
+    pub mirrors: String,
+    /// Empty for a direct connection.
+    pub proxy: String,
+    pub accept_invalid_certs: bool,
+    pub force_ipv4: bool,
+    pub http1_only: bool,
+    /// Connect to the provider on startup so the first image comes sooner,
+    /// see [`AsyncFetcher::warm_up`](crate::AsyncFetcher::warm_up).
+    pub warm_up: bool,
+    /// Download speed cap in KB/s, zero for unlimited.
+    pub throttle_kbps: usize,
+    /// Requests per second to each host, zero for unlimited.
+    pub host_rate_limit: u32,
+    pub compression: bool,
+    /// Warn about fetched images narrower than this, zero doesn't check.
+    pub min_width: u32,
+    /// Warn about fetched images shorter than this, zero doesn't check.
+    pub min_height: u32,
+    /// Stream raw downloads to the system temporary directory.
+    pub spool_to_disk: bool,
+    pub show_hud: bool,
+    pub show_spinner: bool,
+    /// Tell over the image whether it came from the network, the cache or a local file.
+    pub show_source_badge: bool,
+    /// How often to repaint while fetching, zero for every frame.
+    pub repaint_interval_ms: u64,
+    /// How long a new image fades in over the previous one, zero to switch at once.
+    pub crossfade_ms: u64,
+    /// Points per pixel in percent, zero follows the display's scale.
+    pub ui_scale_percent: u32,
+    /// Show images shown smaller than they are through a copy resampled to that size,
+    /// see [`DisplayCopy`](crate::texture::DisplayCopy).
+    pub sharp_downscale: bool,
+    pub downscale_filter: ResizeFilter,
+    /// Only applies on the next start, eframe can't change it on a running window.
+    pub always_on_top: bool,
+    pub decorations: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let config = FetchConfig::default();
+        Self {
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+            max_image_mb: config.max_image_bytes / (1024 * 1024),
+            deadline_secs: config.deadline.as_secs(),
+            offline: config.offline,
+            prefer_webp: config.prefers_webp(),
+            progressive_preview: config.progressive_preview,
+            adaptive_image_size: true,
+            auto_retry: false,
+            auto_retry_attempts: 3,
+            provider: 0,
+            mirrors: String::new(),
+            proxy: String::new(),
+            accept_invalid_certs: config.accept_invalid_certs,
+            force_ipv4: config.force_ipv4,
+            http1_only: config.http1_only,
+            warm_up: false,
+            throttle_kbps: 0,
+            host_rate_limit: 0,
+            compression: config.compression,
+            min_width: 0,
+            min_height: 0,
+            spool_to_disk: config.spool_to_disk.is_some(),
+            show_hud: false,
+            show_spinner: true,
+            show_source_badge: true,
+            repaint_interval_ms: config.repaint_interval.as_millis() as u64,
+            crossfade_ms: 200,
+            ui_scale_percent: 0,
+            sharp_downscale: false,
+            downscale_filter: ResizeFilter::default(),
+            always_on_top: false,
+            decorations: true,
+        }
+    }
+}
+
+impl Settings {
+    pub const STORAGE_KEY: &'static str = "settings";
+
+    /// The saved settings, or the defaults.
+    pub fn load(storage: Option<&dyn Storage>) -> Self {
+        storage
+            .and_then(|storage| eframe::get_value(storage, Self::STORAGE_KEY))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) {
+        eframe::set_value(storage, Self::STORAGE_KEY, self);
+    }
+
+    /// Copy the per fetch options into `config`, they apply on the next fetch.
+    ///
+    /// Client-level ones (proxy, certificates, IPv4, HTTP/1.1) need the client to be rebuilt, see
+    /// [`AsyncFetcher::set_proxy`](crate::AsyncFetcher::set_proxy).
+    pub fn apply_to(&self, config: &mut FetchConfig) {
+        config.max_image_bytes = self.max_image_mb * 1024 * 1024;
+        config.deadline = Duration::from_secs(self.deadline_secs);
+        config.offline = self.offline;
+        config.progressive_preview = self.progressive_preview;
+        config.repaint_interval = Duration::from_millis(self.repaint_interval_ms);
+        config.max_bytes_per_sec = (self.throttle_kbps > 0).then(|| self.throttle_kbps * 1024);
+        config.host_rate_limit = (self.host_rate_limit > 0).then(|| self.host_rate_limit as f64);
+        config.compression = self.compression;
+        config.spool_to_disk = self.spool_to_disk.then(std::env::temp_dir);
+        config.min_dimensions =
+            (self.min_width > 0 || self.min_height > 0).then(|| (self.min_width, self.min_height));
+        if config.prefers_webp() != self.prefer_webp {
+            config.set_prefer_webp(self.prefer_webp);
+        }
+    }
+
+    /// Pixels per point to lay the UI out with, the display's `native` one (1.0 if unknown)
+    /// unless [`ui_scale_percent`](Self::ui_scale_percent) overrides it, 50% at the least.
+    pub fn pixels_per_point(&self, native: Option<f32>) -> f32 {
+        match self.ui_scale_percent {
+            0 => native.unwrap_or(1.0),
+            percent => percent.max(50) as f32 / 100.0,
+        }
+    }
+
+    /// The mirror base URLs, blank lines left out.
+    pub fn mirrors(&self) -> Vec<String> {
+        self.mirrors
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// The proxy setting as the fetcher takes it.
+    pub fn proxy(&self) -> Option<String> {
+        let proxy = self.proxy.trim();
+        (!proxy.is_empty()).then(|| proxy.to_owned())
+    }
+}
+
+/// Read-only view of the file eframe persists its storage in.
+///
+/// eframe only hands its storage over once the window is open, too late for window
+/// options like [`Settings::always_on_top`]. Changes are kept in memory, never written.
+#[derive(Default)]
+pub struct StartupStorage(HashMap<String, String>);
+
+impl StartupStorage {
+    /// The storage eframe uses for `app_name` (the `run_native` one), empty on first launch.
+    pub fn open(app_name: &str) -> Self {
+        match directories_next::ProjectDirs::from("", "", app_name) {
+            Some(dirs) => Self::from_ron_file(dirs.data_dir().join("app.ron")),
+            None => Self::default(),
+        }
+    }
+
+    /// Read a storage file as written by eframe, empty if missing or unreadable.
+    pub fn from_ron_file(path: impl AsRef<Path>) -> Self {
+        let kv = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|ron| ron::from_str(&ron).ok())
+            .unwrap_or_default();
+        Self(kv)
+    }
+}
+
+impl Storage for StartupStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.0.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_owned(), value);
+    }
+
+    fn flush(&mut self) {}
+}
@@ -0,0 +1,385 @@
+use crate::filter::{from_rgba_image, to_rgba_image};
+use eframe::egui::{self, ColorImage, TextureFilter, TextureHandle};
+use image::imageops::{self, FilterType};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// An image uploaded to egui with [`egui::Context::load_texture`].
+///
+/// Replaces `egui_extras::RetainedImage` while keeping the same display helpers,
+/// so `width()/height()/size_vec2()/show_max_size()` usages keep working.
+///
+/// Cloning is cheap, the texture is shared.
+#[derive(Clone)]
+pub struct TextureImage {
+    debug_name: String,
+    texture: TextureHandle,
+}
+
+impl TextureImage {
+    /// Upload already decoded pixels as a texture.
+    pub fn from_color_image(
+        ctx: &egui::Context,
+        debug_name: impl Into<String>,
+        image: ColorImage,
+    ) -> Self {
+        let debug_name = debug_name.into();
+        let texture = ctx.load_texture(&debug_name, image, TextureFilter::Linear);
+        Self {
+            debug_name,
+            texture,
+        }
+    }
+
+    /// Decode a (non-svg) image and upload it as a texture.
+    pub fn from_image_bytes(
+        ctx: &egui::Context,
+        debug_name: impl Into<String>,
+        image_bytes: &[u8],
+    ) -> Result<Self, String> {
+        let image = egui_extras::image::load_image_bytes(image_bytes)?;
+        Ok(Self::from_color_image(ctx, debug_name, image))
+    }
+
+    /// The debug name of the image, e.g. the URL.
+    pub fn debug_name(&self) -> &str {
+        &self.debug_name
+    }
+
+    /// The texture handle of this image.
+    pub fn texture(&self) -> &TextureHandle {
+        &self.texture
+    }
+
+    /// Shared by the clones of this image, tells whether two images are the same texture.
+    pub fn id(&self) -> egui::TextureId {
+        self.texture.id()
+    }
+
+    pub fn size(&self) -> [usize; 2] {
+        self.texture.size()
+    }
+
+    pub fn width(&self) -> usize {
+        self.size()[0]
+    }
+
+    pub fn height(&self) -> usize {
+        self.size()[1]
+    }
+
+    pub fn size_vec2(&self) -> egui::Vec2 {
+        self.texture.size_vec2()
+    }
+
+    /// Show the image with the given maximum size.
+    pub fn show_max_size(&self, ui: &mut egui::Ui, max_size: egui::Vec2) -> egui::Response {
+        let mut desired_size = self.size_vec2();
+        desired_size *= (max_size.x / desired_size.x).min(1.0);
+        desired_size *= (max_size.y / desired_size.y).min(1.0);
+        self.show_size(ui, desired_size)
+    }
+
+    /// Show the image with the given size.
+    pub fn show_size(&self, ui: &mut egui::Ui, desired_size: egui::Vec2) -> egui::Response {
+        ui.image(self.texture.id(), desired_size)
+    }
+
+    /// Same as [`show_size`](Self::show_size), only `opacity` (0 to 1) opaque.
+    pub fn show_faded(
+        &self,
+        ui: &mut egui::Ui,
+        desired_size: egui::Vec2,
+        opacity: f32,
+    ) -> egui::Response {
+        let tint = egui::Rgba::from_white_alpha(opacity.clamp(0.0, 1.0));
+        ui.add(egui::Image::new(self.texture.id(), desired_size).tint(tint))
+    }
+}
+
+/// The previous image fading out while the new one fades in.
+pub struct Crossfade {
+    previous: TextureImage,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Crossfade {
+    /// Start fading `previous` out at `now`, over `duration`.
+    pub fn new(previous: TextureImage, duration: Duration, now: Instant) -> Self {
+        Self {
+            previous,
+            started: now,
+            duration,
+        }
+    }
+
+    pub fn previous(&self) -> &TextureImage {
+        &self.previous
+    }
+
+    /// Opacity of the new image at `now`, eased from 0 (previous image only) to 1 (done).
+    pub fn opacity(&self, now: Instant) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        let elapsed = now.saturating_duration_since(self.started);
+        let t = (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0);
+        // Smoothstep, so neither image pops in or out.
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    pub fn is_done(&self, now: Instant) -> bool {
+        self.opacity(now) >= 1.0
+    }
+
+    /// Paint the previous image over `rect`, where the new image is shown at `now`.
+    ///
+    /// It keeps its aspect ratio, fitted and centered in `rect`.
+    pub fn paint_previous(&self, painter: &egui::Painter, rect: egui::Rect, now: Instant) {
+        let size = self.previous.size_vec2();
+        let scale = (rect.width() / size.x).min(rect.height() / size.y);
+        if !scale.is_finite() || scale <= 0.0 {
+            return;
+        }
+        let previous_rect = egui::Rect::from_center_size(rect.center(), size * scale);
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        let tint = egui::Rgba::from_white_alpha(1.0 - self.opacity(now));
+        painter.add(egui::Shape::image(
+            self.previous.id(),
+            previous_rect,
+            uv,
+            tint.into(),
+        ));
+    }
+}
+
+/// Resampling filter of the [`DisplayCopy`] of images shown smaller than they are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResizeFilter {
+    /// Bilinear, the fastest, about what the GPU does.
+    Triangle,
+    /// Bicubic, sharper.
+    CatmullRom,
+    /// The sharpest, and the slowest.
+    Lanczos3,
+}
+
+impl Default for ResizeFilter {
+    fn default() -> Self {
+        Self::Lanczos3
+    }
+}
+
+impl ResizeFilter {
+    pub const ALL: [Self; 3] = [Self::Triangle, Self::CatmullRom, Self::Lanczos3];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Triangle => "Bilinear",
+            Self::CatmullRom => "Bicubic",
+            Self::Lanczos3 => "Lanczos",
+        }
+    }
+
+    fn filter_type(self) -> FilterType {
+        match self {
+            Self::Triangle => FilterType::Triangle,
+            Self::CatmullRom => FilterType::CatmullRom,
+            Self::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+/// Images shown at more than this fraction of their size are left to the texture filtering,
+/// a display copy wouldn't look any different.
+pub const DOWNSCALE_BELOW: f32 = 0.9;
+
+/// Size to resample an `image_size` image to when it's shown `shown` pixels (not points) big,
+/// aspect ratio kept and rounded up.
+///
+/// `None` unless it's shown smaller than [`DOWNSCALE_BELOW`] times its size.
+pub fn downscale_target(image_size: [usize; 2], shown: egui::Vec2) -> Option<[usize; 2]> {
+    let [width, height] = image_size;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let scale = (shown.x / width as f32).max(shown.y / height as f32);
+    if !scale.is_finite() || scale <= 0.0 || scale >= DOWNSCALE_BELOW {
+        return None;
+    }
+    let side = |len: usize| ((len as f32 * scale).ceil() as usize).max(1);
+    Some([side(width), side(height)])
+}
+
+/// A copy of an image resampled down to the size it's shown at, sharper than the texture
+/// filtering of the full size one and smaller to draw.
+///
+/// The original stays around, it's shown instead when zooming in past the copy's size,
+/// see [`display_texture`].
+#[derive(Clone)]
+pub struct DisplayCopy {
+    source: egui::TextureId,
+    filter: ResizeFilter,
+    image: TextureImage,
+}
+
+impl DisplayCopy {
+    /// Resample `pixels`, the ones of `source`, to `target` with `filter` and upload the result.
+    ///
+    /// This is blocking work, slow for big images with Lanczos.
+    pub fn new(
+        ctx: &egui::Context,
+        source: &TextureImage,
+        pixels: &ColorImage,
+        target: [usize; 2],
+        filter: ResizeFilter,
+    ) -> Self {
+        let [width, height] = target;
+        let resized = imageops::resize(
+            &to_rgba_image(pixels),
+            width.max(1) as u32,
+            height.max(1) as u32,
+            filter.filter_type(),
+        );
+        let name = format!("{} (display copy)", source.debug_name());
+        Self {
+            source: source.id(),
+            filter,
+            image: TextureImage::from_color_image(ctx, name, from_rgba_image(&resized)),
+        }
+    }
+
+    /// The texture of the image it was made from.
+    pub fn source(&self) -> egui::TextureId {
+        self.source
+    }
+
+    pub fn filter(&self) -> ResizeFilter {
+        self.filter
+    }
+
+    pub fn image(&self) -> &TextureImage {
+        &self.image
+    }
+}
+
+/// What to draw for `image` shown `shown` pixels big: its display copy when there's
+/// one of it, big enough not to be scaled up, else the image itself.
+pub fn display_texture<'a>(
+    image: &'a TextureImage,
+    copy: Option<&'a DisplayCopy>,
+    shown: egui::Vec2,
+) -> &'a TextureImage {
+    match copy {
+        Some(copy) if copy.source == image.id() => {
+            let size = copy.image.size_vec2();
+            // Rounding of the shown size aside.
+            if size.x + 1.0 >= shown.x && size.y + 1.0 >= shown.y {
+                &copy.image
+            } else {
+                image
+            }
+        }
+        _ => image,
+    }
+}
+
+/// How an image is sized to the room it's shown in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitMode {
+    /// One texel per physical pixel, bigger images scroll.
+    ActualSize,
+    /// As big as possible while fully visible, smaller images are scaled up.
+    Window,
+    /// As wide as the room, taller images scroll vertically.
+    Width,
+}
+
+impl Default for FitMode {
+    fn default() -> Self {
+        Self::ActualSize
+    }
+}
+
+impl FitMode {
+    pub const ALL: [FitMode; 3] = [FitMode::Window, FitMode::ActualSize, FitMode::Width];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ActualSize => "Actual size (100%)",
+            Self::Window => "Fit to window",
+            Self::Width => "Fit width",
+        }
+    }
+
+    /// Size in points to show an `image_size` pixels image with, given the `available` points.
+    ///
+    /// An empty or unbounded room falls back to the actual size.
+    pub fn display_size(
+        self,
+        image_size: egui::Vec2,
+        available: egui::Vec2,
+        pixels_per_point: f32,
+    ) -> egui::Vec2 {
+        let actual = image_size / pixels_per_point;
+        let scale = match self {
+            Self::ActualSize => 1.0,
+            Self::Window => (available.x / actual.x).min(available.y / actual.y),
+            Self::Width => available.x / actual.x,
+        };
+        if scale.is_finite() && scale > 0.0 {
+            actual * scale
+        } else {
+            actual
+        }
+    }
+}
+
+/// Requested image sizes are rounded to a multiple of this, small resizes keep the same URL.
+pub const IMAGE_SIZE_STEP: usize = 128;
+/// Smallest image size requested, however small the window.
+pub const MIN_IMAGE_SIZE: usize = 256;
+/// Largest image size requested, bigger images are slow to decode in debug builds.
+pub const MAX_IMAGE_SIZE: usize = 2048;
+
+/// Side of the square image to request to fill `available` pixels (not points).
+///
+/// The shorter side is rounded to the nearest [`IMAGE_SIZE_STEP`],
+/// within [`MIN_IMAGE_SIZE`] and [`MAX_IMAGE_SIZE`].
+pub fn requested_image_size(available: egui::Vec2) -> usize {
+    let side = available.x.min(available.y).max(0.0) as usize;
+    let rounded = (side + IMAGE_SIZE_STEP / 2) / IMAGE_SIZE_STEP * IMAGE_SIZE_STEP;
+    rounded.clamp(MIN_IMAGE_SIZE, MAX_IMAGE_SIZE)
+}
+
+/// Estimated memory taken by textures of `sizes` (width, height), 4 bytes per texel.
+pub fn texture_memory(sizes: impl IntoIterator<Item = [usize; 2]>) -> usize {
+    sizes
+        .into_iter()
+        .map(|[width, height]| width * height * 4)
+        .sum()
+}
+
+/// Pixel of an `image_size` image under `pos`, when the image is painted at `rect`.
+///
+/// Both are in screen points, so scrolling and scaling are already in `rect`.
+/// `None` outside of the image, the right and bottom edges belong to the last pixels.
+pub fn pixel_at(pos: egui::Pos2, rect: egui::Rect, image_size: [usize; 2]) -> Option<[usize; 2]> {
+    let [width, height] = image_size;
+    if width == 0 || height == 0 || rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return None;
+    }
+    if !rect.contains(pos) {
+        return None;
+    }
+    let x = (pos.x - rect.min.x) / rect.width() * width as f32;
+    let y = (pos.y - rect.min.y) / rect.height() * height as f32;
+    Some([(x as usize).min(width - 1), (y as usize).min(height - 1)])
+}
+
+/// `#rrggbbaa` of a pixel, alpha unmultiplied like image editors show it.
+pub fn color_hex(color: egui::Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+}
@@ -0,0 +1,118 @@
+use crate::{
+    decode::{decode_thumbnail, thumbnail},
+    fetcher::fetch_data,
+    priority::Priority,
+    progress::ProgressSink,
+    texture::TextureImage,
+    AsyncFetcher, CancelReason, FetchError,
+};
+use async_trait::async_trait;
+use flowync::{error::Compact, CompactFlower, CompactHandle};
+use tokio::time;
+
+/// Side of the box thumbnails are fit in, in pixels.
+pub const THUMBNAIL_SIZE: u32 = 64;
+
+type ThumbnailHandle = CompactHandle<(), TextureImage, String>;
+
+/// Loads one thumbnail at a time for the history strip.
+///
+/// Images still in the fetcher's cache are downscaled from there, others are downloaded
+/// as raw data and decoded straight to the small size, so long histories don't keep
+/// full size images around.
+pub struct ThumbnailJob {
+    flower: CompactFlower<(), TextureImage, String>,
+}
+
+// Only cancelation matters, nothing shows a thumbnail's progress.
+struct CancelSink<'a>(&'a ThumbnailHandle);
+
+#[async_trait]
+impl ProgressSink for CancelSink<'_> {
+    async fn on_bytes(&self, _chunk_len: usize) {}
+
+    async fn on_total(&self, _total: usize) {}
+
+    fn should_cancel(&self) -> bool {
+        self.0.should_cancel()
+    }
+}
+
+impl ThumbnailJob {
+    pub fn new() -> Self {
+        Self {
+            flower: CompactFlower::new(1),
+        }
+    }
+
+    /// Start loading the thumbnail of `url`, fitting in [`THUMBNAIL_SIZE`].
+    ///
+    /// Shares the fetcher's limiter, client and per host rate limit.
+    pub fn spawn(&self, fetcher: &AsyncFetcher, url: String) {
+        let handle = self.flower.handle();
+        handle.activate();
+        let ctx = fetcher.ctx.clone();
+        let cached = fetcher.cache().get(&url);
+        let limiter = fetcher.limiter();
+        let priority = fetcher.priority.clone();
+        let client = fetcher.client.clone();
+        let rate_limiter = fetcher.rate_limiter.clone();
+        let config = fetcher.config().clone();
+        fetcher.runtime_handle().spawn(async move {
+            let pixels = match cached {
+                // Already decoded, only downscale it.
+                Some(entry) => blocking(move || Ok(thumbnail(&entry.pixels, THUMBNAIL_SIZE))).await,
+                None => {
+                    let _permit = priority.acquire(limiter, Priority::Background).await;
+                    if let Some(per_sec) = config.host_rate_limit {
+                        time::sleep(rate_limiter.reserve(&url, per_sec)).await;
+                    }
+                    let sink = CancelSink(&handle);
+                    let fetch = fetch_data(url.clone(), &client, &config, &sink);
+                    match time::timeout(config.deadline, fetch).await {
+                        Ok(Ok(bytes)) => {
+                            blocking(move || decode_thumbnail(&bytes, THUMBNAIL_SIZE)).await
+                        }
+                        Ok(Err(e)) => Err(e),
+                        Err(_) => Err(CancelReason::Timeout(config.deadline).into()),
+                    }
+                }
+            };
+            match pixels {
+                Ok(pixels) => handle.success(TextureImage::from_color_image(&ctx, url, pixels)),
+                Err(e) => handle.error(e.to_string()),
+            }
+            // Nothing else may be repainting meanwhile.
+            ctx.request_repaint();
+        });
+    }
+
+    /// Check if a thumbnail is being loaded.
+    pub fn is_active(&self) -> bool {
+        self.flower.is_active()
+    }
+
+    /// Take the thumbnail once loaded, should be called once per frame.
+    pub fn poll(&self) -> Option<Result<TextureImage, String>> {
+        let mut result = None;
+        self.flower.try_result(|r| {
+            result = Some(r.map_err(|e| match e {
+                Compact::Suppose(e) | Compact::Panicked(e) => e,
+            }))
+        });
+        result
+    }
+}
+
+// Run `f` off the async workers.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, FetchError> + Send + 'static,
+) -> Result<T, FetchError> {
+    tokio::task::spawn_blocking(f).await?
+}
+
+impl Default for ThumbnailJob {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,195 @@
+use eframe::egui;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// How long a toast stays up by default, fade-out included.
+pub const TOAST_DURATION: Duration = Duration::from_secs(3);
+/// Toasts fade out over the end of their duration.
+pub const TOAST_FADE: Duration = Duration::from_millis(500);
+/// Most toasts shown at once, the oldest ones go first.
+pub const MAX_TOASTS: usize = 5;
+
+/// What a toast is about, sets its icon and color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastKind {
+    fn icon(self) -> &'static str {
+        match self {
+            Self::Info => "ℹ",
+            Self::Success => "✔",
+            Self::Warning => "⚠",
+            Self::Error => "✖",
+        }
+    }
+
+    fn color(self, visuals: &egui::Visuals) -> egui::Color32 {
+        match self {
+            Self::Info => visuals.text_color(),
+            Self::Success => egui::Color32::from_rgb(90, 200, 90),
+            Self::Warning => visuals.warn_fg_color,
+            Self::Error => visuals.error_fg_color,
+        }
+    }
+}
+
+/// A short message, dismissed once its duration is over.
+#[derive(Clone, Debug)]
+pub struct Toast {
+    pub kind: ToastKind,
+    pub text: String,
+    created: Instant,
+    duration: Duration,
+}
+
+impl Toast {
+    pub fn is_expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.created) >= self.duration
+    }
+
+    /// Opacity at `now`, fading from 1 to 0 over the last [`TOAST_FADE`] of its duration.
+    pub fn opacity(&self, now: Instant) -> f32 {
+        let elapsed = now.saturating_duration_since(self.created);
+        let left = self.duration.saturating_sub(elapsed);
+        let fade = TOAST_FADE.min(self.duration);
+        if left.is_zero() {
+            0.0
+        } else if left >= fade {
+            1.0
+        } else {
+            left.as_secs_f32() / fade.as_secs_f32()
+        }
+    }
+
+    // How long until it starts fading out, zero once it has.
+    fn until_fade(&self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.created);
+        self.duration
+            .saturating_sub(TOAST_FADE.min(self.duration))
+            .saturating_sub(elapsed)
+    }
+}
+
+/// Toasts stacked in the bottom-right corner of the window, newest at the bottom.
+///
+/// Outcomes of async work (saves, failed fetches, retries...) are pushed as they come,
+/// [`show`](Self::show) paints them every frame and drops the expired ones.
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+    duration: Duration,
+}
+
+impl Toasts {
+    /// Toasts pushed from now on stay up for `duration`.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            toasts: VecDeque::new(),
+            duration,
+        }
+    }
+
+    /// Show `text` from `now` on, past [`MAX_TOASTS`] the oldest toast is dropped.
+    pub fn push_at(&mut self, kind: ToastKind, text: impl ToString, now: Instant) {
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            kind,
+            text: text.to_string(),
+            created: now,
+            duration: self.duration,
+        });
+    }
+
+    pub fn push(&mut self, kind: ToastKind, text: impl ToString) {
+        self.push_at(kind, text, Instant::now());
+    }
+
+    pub fn info(&mut self, text: impl ToString) {
+        self.push(ToastKind::Info, text);
+    }
+
+    pub fn success(&mut self, text: impl ToString) {
+        self.push(ToastKind::Success, text);
+    }
+
+    pub fn warning(&mut self, text: impl ToString) {
+        self.push(ToastKind::Warning, text);
+    }
+
+    pub fn error(&mut self, text: impl ToString) {
+        self.push(ToastKind::Error, text);
+    }
+
+    /// Drop the toasts whose duration is over at `now`.
+    pub fn expire(&mut self, now: Instant) {
+        self.toasts.retain(|toast| !toast.is_expired(now));
+    }
+
+    /// The toasts up at `now`, oldest first.
+    pub fn visible(&self, now: Instant) -> impl Iterator<Item = &Toast> {
+        self.toasts
+            .iter()
+            .filter(move |toast| !toast.is_expired(now))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+
+    /// Paint the toasts and expire the old ones.
+    ///
+    /// A repaint is requested for the next fade-out, then every frame while it fades.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        self.expire(now);
+        let next_fade = match self.toasts.iter().map(|toast| toast.until_fade(now)).min() {
+            Some(next_fade) => next_fade,
+            None => return,
+        };
+        if next_fade.is_zero() {
+            ctx.request_repaint();
+        } else {
+            ctx.request_repaint_after(next_fade);
+        }
+        egui::Area::new("toasts")
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.set_max_width(320.0);
+                for toast in &self.toasts {
+                    let opacity = toast.opacity(now);
+                    let visuals = ui.visuals();
+                    let fill = visuals.window_fill().linear_multiply(opacity);
+                    let color = toast.kind.color(visuals).linear_multiply(opacity);
+                    let text_color = visuals.text_color().linear_multiply(opacity);
+                    // No shadow, it wouldn't fade along.
+                    egui::Frame::none()
+                        .fill(fill)
+                        .stroke(egui::Stroke::new(1.0, color))
+                        .rounding(4.0)
+                        .inner_margin(egui::style::Margin::symmetric(8.0, 4.0))
+                        .show(ui, |ui| {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.colored_label(color, toast.kind.icon());
+                                ui.colored_label(text_color, &toast.text);
+                            });
+                        });
+                }
+            });
+    }
+}
+
+impl Default for Toasts {
+    fn default() -> Self {
+        Self::new(TOAST_DURATION)
+    }
+}
@@ -1,49 +1,744 @@
-use egui_extras::RetainedImage;
+use crate::{
+    animation::Animation,
+    filter::ImageFilter,
+    http::version_label,
+    texture::{DisplayCopy, TextureImage},
+    FetchError,
+};
+use eframe::egui::{self, ColorImage};
+use rand::Rng;
+use reqwest::{header::HeaderMap, Version};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+use tempfile::NamedTempFile;
 #[allow(dead_code)]
 pub enum Channel {
     Data(usize),
     Image(usize),
+    // No chunk arrived within the stall timeout.
+    ImageStalled,
+    // Partially decoded image, while the download goes on.
+    ImagePreview(TextureImage),
+    // Size announced by the server (Content-Length), sent before the first chunk.
+    ImageTotal(usize),
+    // Headers of the response, sent as soon as it comes.
+    ImageHeaders(HeaderMap),
+    // Download done, decoding the image.
+    ImageDecoding,
+    // Image decoded, the result (or cancelation) comes next.
+    ImageDecoded,
+    // Image decoded, how long each step took. Sent right before the result.
+    ImageTiming(FetchTiming),
+    // Served from the cache instead of downloaded. Sent right before the result.
+    ImageCached,
+    // The URL being fetched failed, trying this mirror of it instead.
+    Mirror(String),
+    // Waiting this long for the host's turn, see `FetchConfig::host_rate_limit`.
+    RateLimited(Duration),
+    // The raw data came compressed, sent once decompressed right before the result.
+    DataCompressed(Compression),
 }
 
 #[allow(dead_code)]
+#[derive(Debug)]
 pub enum ErrCause {
-    Data(String),
-    Image(String),
+    Data(FetchError),
+    Image(FetchError),
 }
 
 #[allow(dead_code)]
 pub enum Container {
     Data(Vec<u8>),
-    Image(RetainedImage),
+    // Texture, decoded pixels and content hash of the downloaded bytes.
+    Image(TextureImage, ColorImage, u64),
+    // Animated image, the pixels are the first frame's.
+    Animation(Animation, ColorImage, u64),
+    // Same bytes as the known hash, nothing was decoded.
+    Unchanged,
+    // Raw data streamed to disk, see `FetchConfig::spool_to_disk`. Deleted once dropped.
+    File(NamedTempFile),
+}
+
+// A fetch requested while another one was still running.
+pub struct PendingFetch {
+    pub seed: usize,
+    pub next_image: bool,
+}
+
+// Re-spawn failed fetches without waiting for a click, up to `max_attempts` in a row.
+pub struct AutoRetry {
+    pub enabled: bool,
+    pub max_attempts: usize,
+    attempts: usize,
+}
+
+impl Default for AutoRetry {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_attempts: 3,
+            attempts: 0,
+        }
+    }
+}
+
+impl AutoRetry {
+    // A fetch finished with `error` (`None` on success), `true` if it should be spawned again.
+    pub fn on_finished(&mut self, error: Option<&FetchError>) -> bool {
+        match error {
+            Some(e) if self.enabled && e.is_retryable() && self.attempts < self.max_attempts => {
+                self.attempts += 1;
+                true
+            }
+            _ => {
+                self.attempts = 0;
+                false
+            }
+        }
+    }
+
+    // Retries spawned since the last fetch that didn't get one.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+}
+
+// Seeds left behind by prev/next and random picks, like a browser's back and forward.
+// Moves are recorded when requested, `undo` takes back one that didn't show its image.
+#[derive(Default)]
+pub struct SeedHistory {
+    back: Vec<usize>,
+    forward: Vec<usize>,
+}
+
+impl SeedHistory {
+    pub const MAX_LEN: usize = 100;
+
+    // Go back from `current` to where it came from, or to `fallback` (the previous seed)
+    // when there's no history. `None` if there's nowhere to go.
+    pub fn prev(&mut self, current: usize, fallback: Option<usize>) -> Option<usize> {
+        let seed = self.back.pop().or(fallback)?;
+        push_capped(&mut self.forward, current);
+        Some(seed)
+    }
+
+    // Same as `prev`, forward.
+    pub fn next(&mut self, current: usize, fallback: Option<usize>) -> Option<usize> {
+        let seed = self.forward.pop().or(fallback)?;
+        push_capped(&mut self.back, current);
+        Some(seed)
+    }
+
+    // Jump from `current` to `seed`, counted as a move forward: prev comes back to `current`.
+    pub fn jump(&mut self, current: usize, seed: usize) -> usize {
+        push_capped(&mut self.back, current);
+        self.forward.clear();
+        seed
+    }
+
+    // Jump from `current` to a random seed of `range`, see `random_seed`.
+    pub fn random(
+        &mut self,
+        current: usize,
+        range: RangeInclusive<usize>,
+        rng: &mut impl Rng,
+    ) -> usize {
+        self.jump(current, random_seed(range, current, rng))
+    }
+
+    // The move to `failed` (forward if `next_image`) didn't show it: the seed it came from,
+    // the same move leads to `failed` again.
+    pub fn undo(&mut self, next_image: bool, failed: usize) -> Option<usize> {
+        let (from, to) = if next_image {
+            (&mut self.back, &mut self.forward)
+        } else {
+            (&mut self.forward, &mut self.back)
+        };
+        let seed = from.pop()?;
+        push_capped(to, failed);
+        Some(seed)
+    }
+
+    pub fn clear(&mut self) {
+        self.back.clear();
+        self.forward.clear();
+    }
+}
+
+fn push_capped(seeds: &mut Vec<usize>, seed: usize) {
+    if seeds.len() == SeedHistory::MAX_LEN {
+        seeds.remove(0);
+    }
+    seeds.push(seed);
+}
+
+// A random seed of `range`, never `current` unless it's the only one.
+pub fn random_seed(range: RangeInclusive<usize>, current: usize, rng: &mut impl Rng) -> usize {
+    let (first, last) = range.into_inner();
+    if first >= last {
+        return first;
+    }
+    if !(first..=last).contains(&current) {
+        return rng.gen_range(first..=last);
+    }
+    // Pick among the others, stepping over `current`.
+    let seed = rng.gen_range(first..last);
+    if seed >= current {
+        seed + 1
+    } else {
+        seed
+    }
+}
+
+// Which of the prev/next buttons a seed fetch comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Prev,
+    Next,
+}
+
+impl Direction {
+    pub fn from_next_image(next_image: bool) -> Self {
+        if next_image {
+            Self::Next
+        } else {
+            Self::Prev
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Prev => "prev",
+            Self::Next => "next",
+        }
+    }
+}
+
+// Where the seed fetches are at, telling what the prev/next buttons say.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiMode {
+    Idle,
+    Fetching(Direction),
+    // The failed fetch is spawned again, `attempt` of `max`.
+    Retrying {
+        direction: Direction,
+        attempt: usize,
+        max: usize,
+    },
+    // The failed fetch is spawned again in `secs`, once the server allows it.
+    RateLimited {
+        direction: Direction,
+        secs: u64,
+    },
+    // The fetch is canceled, the same button fetches it again.
+    Canceled(Direction),
+    // The server has no image for the seed.
+    NotFound(Direction),
+    // There's no seed to go to that way.
+    Unavailable(Direction),
+}
+
+impl Default for UiMode {
+    fn default() -> Self {
+        Self::Idle
+    }
+}
+
+impl UiMode {
+    // The seed fetch from `direction` is finalized, and wasn't retried.
+    pub fn finished(direction: Direction, canceled: bool, error: Option<&FetchError>) -> Self {
+        if canceled {
+            Self::Canceled(direction)
+        } else if matches!(error, Some(FetchError::NotFound)) {
+            Self::NotFound(direction)
+        } else {
+            Self::Idle
+        }
+    }
+
+    // The one button the mode is about, if any.
+    pub fn direction(self) -> Option<Direction> {
+        match self {
+            Self::Idle => None,
+            Self::Fetching(direction)
+            | Self::Retrying { direction, .. }
+            | Self::RateLimited { direction, .. }
+            | Self::Canceled(direction)
+            | Self::NotFound(direction)
+            | Self::Unavailable(direction) => Some(direction),
+        }
+    }
+
+    // Whether the button of `direction` can be clicked, not once it found nowhere to go.
+    // Any other move enables it again.
+    pub fn is_enabled(self, direction: Direction) -> bool {
+        self != Self::Unavailable(direction)
+    }
+
+    // What the button of `direction` says.
+    pub fn label(self, direction: Direction) -> String {
+        if self.direction() != Some(direction) {
+            return format!("Fetch {} image", direction.name());
+        }
+        match self {
+            Self::Retrying { attempt, max, .. } => format!("Retrying ({}/{})...", attempt, max),
+            Self::RateLimited { secs, .. } => format!("Rate limited, retrying in {}s...", secs),
+            Self::Canceled(_) => format!("Retry {} image?", direction.name()),
+            Self::NotFound(_) => "No image for this seed".into(),
+            Self::Unavailable(Direction::Prev) => "Prev image not available".into(),
+            Self::Unavailable(Direction::Next) => "Next image not available".into(),
+            Self::Idle | Self::Fetching(_) => format!("Fetch {} image", direction.name()),
+        }
+    }
+}
+
+// Session wide fetch statistics.
+#[derive(Default, Serialize)]
+pub struct FetchStats {
+    pub total_bytes: usize,
+    pub successes: usize,
+    pub failures: usize,
+    pub cancellations: usize,
+    // Raw downloads that came compressed, and the bytes that weren't transferred thanks to it.
+    pub compressed: usize,
+    pub saved_bytes: usize,
+}
+
+// Time elapsed since the request was sent, at each step of a fetch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FetchTiming {
+    // Response headers received, connecting included.
+    pub first_byte: Duration,
+    pub last_byte: Duration,
+    pub decoded: Duration,
+    // HTTP version the response came over, when the HTTP client tells.
+    pub http_version: Option<Version>,
+}
+
+impl FetchTiming {
+    // Time to first byte.
+    pub fn ttfb(&self) -> Duration {
+        self.first_byte
+    }
+
+    pub fn download(&self) -> Duration {
+        self.last_byte.saturating_sub(self.first_byte)
+    }
+
+    pub fn decode(&self) -> Duration {
+        self.decoded.saturating_sub(self.last_byte)
+    }
+}
+
+impl fmt::Display for FetchTiming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(version) = self.http_version {
+            write!(f, "{}, ", version_label(version))?;
+        }
+        write!(
+            f,
+            "TTFB {}ms, download {}ms, decode {}ms",
+            self.ttfb().as_millis(),
+            self.download().as_millis(),
+            self.decode().as_millis()
+        )
+    }
+}
+
+// How much a compressed response saved, see `FetchConfig::compression`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Compression {
+    // The `Content-Encoding`, e.g. "gzip".
+    pub encoding: &'static str,
+    // Received over the network, progress is reported in these.
+    pub wire_bytes: usize,
+    pub decoded_bytes: usize,
+}
+
+impl Compression {
+    pub fn saved(&self) -> usize {
+        self.decoded_bytes.saturating_sub(self.wire_bytes)
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, {} transferred for {}",
+            self.encoding,
+            human_bytes(self.wire_bytes),
+            human_bytes(self.decoded_bytes)
+        )
+    }
+}
+
+// Recently fetched URLs, newest first and without duplicates.
+#[derive(Default)]
+pub struct History {
+    pub urls: Vec<String>,
+}
+
+impl History {
+    pub const MAX_LEN: usize = 50;
+
+    pub fn new(mut urls: Vec<String>) -> Self {
+        urls.truncate(Self::MAX_LEN);
+        Self { urls }
+    }
+
+    pub fn push(&mut self, url: impl Into<String>) {
+        let url = url.into();
+        self.urls.retain(|u| *u != url);
+        self.urls.insert(0, url);
+        self.urls.truncate(Self::MAX_LEN);
+    }
+
+    pub fn clear(&mut self) {
+        self.urls.clear();
+    }
+}
+
+// Sizes of the last images downloaded per requested size, to guess how far along
+// a download is when the server doesn't tell its size.
+#[derive(Default)]
+pub struct SizeEstimator {
+    samples: HashMap<usize, VecDeque<usize>>,
+}
+
+impl SizeEstimator {
+    // Samples kept per requested size, older ones are dropped.
+    pub const MAX_SAMPLES: usize = 20;
+    // An estimate never reaches the end, only the finished download does.
+    pub const MAX_FRACTION: f32 = 0.95;
+
+    pub fn record(&mut self, requested: usize, bytes: usize) {
+        if bytes == 0 {
+            return;
+        }
+        let samples = self.samples.entry(requested).or_default();
+        if samples.len() == Self::MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(bytes);
+    }
+
+    // Average size of the images downloaded for `requested`, `None` without any.
+    pub fn estimate(&self, requested: usize) -> Option<usize> {
+        let samples = self.samples.get(&requested).filter(|s| !s.is_empty())?;
+        Some(samples.iter().sum::<usize>() / samples.len())
+    }
+
+    // Estimated fraction of the download done after `received` bytes, up to `MAX_FRACTION`.
+    pub fn fraction(&self, requested: usize, received: usize) -> Option<f32> {
+        let estimate = self.estimate(requested)?;
+        Some((received as f32 / estimate as f32).min(Self::MAX_FRACTION))
+    }
+}
+
+// Follows a value once it held still for `delay`, e.g. a size while the window is resized.
+pub struct Debounce<T> {
+    value: T,
+    // The latest different value and since when it's been seen.
+    pending: Option<(T, Instant)>,
+    delay: Duration,
+}
+
+impl<T: Copy + PartialEq> Debounce<T> {
+    pub fn new(value: T, delay: Duration) -> Self {
+        Self {
+            value,
+            pending: None,
+            delay,
+        }
+    }
+
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    // Waiting for a new value to settle.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    // Feed the value seen at `now`, returns the settled one.
+    pub fn update(&mut self, value: T, now: Instant) -> T {
+        if value == self.value {
+            self.pending = None;
+            return self.value;
+        }
+        match self.pending {
+            Some((pending, since)) if pending == value => {
+                if now.saturating_duration_since(since) >= self.delay {
+                    self.value = value;
+                    self.pending = None;
+                }
+            }
+            _ => self.pending = Some((value, now)),
+        }
+        self.value
+    }
+}
+
+// Where the current fetch (or local load) of a `NetworkImage` is at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FetchPhase {
+    Idle,
+    Downloading,
+    Decoding,
+    Done,
+    Error,
+}
+
+impl Default for FetchPhase {
+    fn default() -> Self {
+        Self::Idle
+    }
+}
+
+impl FetchPhase {
+    // Downloading or decoding, progress should be shown.
+    pub fn is_busy(self) -> bool {
+        matches!(self, Self::Downloading | Self::Decoding)
+    }
+}
+
+// Where an image came from, shown as a badge over it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageSource {
+    Network,
+    // Not modified since cached, or offline.
+    Cache,
+    // A file, the clipboard or a `data:` URI.
+    Local,
+}
+
+impl Default for ImageSource {
+    fn default() -> Self {
+        Self::Network
+    }
+}
+
+impl ImageSource {
+    pub fn badge(self) -> &'static str {
+        match self {
+            Self::Network => "🌐 network",
+            Self::Cache => "⚡ cache",
+            Self::Local => "📁 local",
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct NetworkImage {
-    pub image: Option<RetainedImage>,
+    pub image: Option<TextureImage>,
+    // Decoded RGBA pixels of the current image, needed for clipboard copy.
+    pub pixels: Option<ColorImage>,
+    // Filtered copy of the current image, shown instead of it when present.
+    pub filtered: Option<(ImageFilter, TextureImage)>,
+    // Sharper copy of the displayed image, at the size it's shown, see `DisplayCopy`.
+    pub display_copy: Option<DisplayCopy>,
+    // Frames of the current image when it's animated, `image` is the first one.
+    pub animation: Option<Animation>,
+    // Partial decode of the running download, if any succeeded.
+    pub preview: Option<TextureImage>,
+    // Size in bytes of the current image.
     pub file_size: usize,
+    // Bytes received so far by the running fetch.
     pub tmp_file_size: usize,
-    pub show_image_progress: bool,
-    pub error: Option<String>,
+    // Size of the running fetch, when the server told it.
+    pub tmp_total: Option<usize>,
+    pub phase: FetchPhase,
+    pub stalled: bool,
+    // The running fetch waits for its host's turn until then.
+    pub rate_limited_until: Option<Instant>,
+    pub error: Option<FetchError>,
+    // Seed of the current image, or of the running fetch.
     pub seed: usize,
+    // First seed found to have no image while fetching forward.
+    pub seed_limit: Option<usize>,
+    // Content hash of the current image bytes.
+    pub hash: Option<u64>,
+    // How the current image was fetched, local loads and cache hits have none.
+    pub timing: Option<FetchTiming>,
+    // Timing reported by the running fetch.
+    pub tmp_timing: Option<FetchTiming>,
+    // Where the current image came from.
+    pub source: Option<ImageSource>,
+    // Where the running fetch gets its image from, network until told otherwise.
+    pub tmp_source: ImageSource,
+    // Response headers of the last fetch, kept when it fails. Local loads have none.
+    pub headers: Option<HeaderMap>,
 }
 
 impl NetworkImage {
-    pub fn set_image(&mut self, image: RetainedImage) {
+    // A new fetch (or local load) started.
+    pub fn start_download(&mut self) {
+        self.error.take();
+        self.headers = None;
+        self.reset_progress();
+        self.phase = FetchPhase::Downloading;
+    }
+
+    // Forget everything about the running fetch, however it ended (or before it starts).
+    // What's shown, the current image and its sizes, is left as is.
+    pub fn reset_progress(&mut self) {
+        self.stalled = false;
+        self.rate_limited_until = None;
+        self.tmp_file_size = 0;
+        self.tmp_total = None;
+        self.tmp_timing = None;
+        self.tmp_source = ImageSource::Network;
+        self.preview = None;
+    }
+
+    pub fn add_bytes(&mut self, len: usize) {
+        self.tmp_file_size += len;
+        self.stalled = false;
+    }
+
+    pub fn set_decoding(&mut self) {
+        self.phase = FetchPhase::Decoding;
+        self.stalled = false;
+    }
+
+    // The fetch succeeded, its size becomes the current file size.
+    pub fn set_image(&mut self, image: TextureImage, pixels: ColorImage, hash: u64) {
         self.error.take();
         self.image = Some(image);
+        self.pixels = Some(pixels);
+        self.filtered = None;
+        self.display_copy = None;
+        self.animation = None;
+        self.hash = Some(hash);
+        self.file_size = self.tmp_file_size;
+        self.timing = self.tmp_timing.take();
+        self.source = Some(self.tmp_source);
+        self.phase = FetchPhase::Done;
+        self.preview = None;
+    }
+
+    // Same as `set_image`, the first frame standing for the image.
+    pub fn set_animation(&mut self, animation: Animation, pixels: ColorImage, hash: u64) {
+        self.set_image(animation.first().clone(), pixels, hash);
+        self.animation = Some(animation);
+    }
+
+    // Decoded size of the current image, servers may not honor the requested size.
+    pub fn image_size(&self) -> Option<[usize; 2]> {
+        self.image.as_ref().map(|image| image.size())
+    }
+
+    // Size of the current image when it's smaller than `min_dimensions` (width, height),
+    // see `FetchConfig::min_dimensions`. Local files are taken as they are.
+    pub fn undersized(&self, min_dimensions: Option<(u32, u32)>) -> Option<[usize; 2]> {
+        let (min_width, min_height) = min_dimensions?;
+        if self.source == Some(ImageSource::Local) {
+            return None;
+        }
+        let [width, height] = self.image_size()?;
+        (width < min_width as usize || height < min_height as usize).then(|| [width, height])
+    }
+
+    // The image to display, the filtered one if any, else the current animation frame.
+    pub fn displayed(&self) -> Option<&TextureImage> {
+        match (&self.filtered, &self.animation) {
+            (Some((_, filtered)), _) => Some(filtered),
+            (None, Some(animation)) => Some(animation.frame()),
+            (None, None) => self.image.as_ref(),
+        }
+    }
+
+    pub fn set_error(&mut self, e: FetchError) {
+        self.error = Some(e);
+        self.phase = FetchPhase::Error;
     }
 
-    pub fn set_error(&mut self, e: impl ToString) {
-        self.error = Some(e.to_string());
+    // The fetch of `seed` got a 404: go back to the seed it came from,
+    // and stop fetching forward past it.
+    pub fn seed_not_found(&mut self, next_image: bool) {
+        if next_image {
+            self.seed_limit = Some(self.seed);
+            self.seed = self.seed.saturating_sub(1);
+        } else {
+            self.seed += 1;
+        }
+    }
+
+    // Whether `seed` is known to have no image.
+    pub fn is_past_seed_limit(&self, seed: usize) -> bool {
+        self.seed_limit.map_or(false, |limit| seed >= limit)
     }
 
+    // Whether the running fetch is still waiting for its host's turn.
+    pub fn is_rate_limited(&self) -> bool {
+        self.rate_limited_until
+            .map_or(false, |until| Instant::now() < until)
+    }
+
+    // Called once the fetch is finalized, whatever the outcome (canceled included).
+    // The current file size only changes with the image, see `set_image`.
     pub fn repair(&mut self) {
-        // Convert final file size in Bytes to KB.
-        if self.tmp_file_size >= 1000 {
-            self.tmp_file_size /= 1000;
-            self.file_size = self.tmp_file_size;
+        if self.phase.is_busy() {
+            // Finished without a new image, e.g. unchanged since the last fetch.
+            self.phase = FetchPhase::Done;
         }
-        self.show_image_progress = false;
-        self.tmp_file_size = 0;
+        self.reset_progress();
+    }
+}
+
+// Ask for a repaint within `interval`, on the next frame when it's zero.
+pub fn request_repaint_within(ctx: &egui::Context, interval: Duration) {
+    if interval.is_zero() {
+        ctx.request_repaint();
+    } else {
+        ctx.request_repaint_after(interval);
+    }
+}
+
+// Same as egui's spinner, repainting within `interval` instead of every frame.
+pub fn spinner(ui: &mut egui::Ui, size: f32, interval: Duration) -> egui::Response {
+    if interval.is_zero() {
+        return ui.add(egui::Spinner::new().size(size));
+    }
+    let (rect, response) = ui.allocate_exact_size(egui::Vec2::splat(size), egui::Sense::hover());
+    if ui.is_rect_visible(rect) {
+        request_repaint_within(ui.ctx(), interval);
+        let radius = rect.height() / 2.0 - 2.0;
+        let time = ui.input().time;
+        let start_angle = time * std::f64::consts::TAU;
+        let end_angle = start_angle + 240f64.to_radians() * time.sin();
+        let points = (0..20)
+            .map(|i| {
+                let angle = egui::lerp(start_angle..=end_angle, i as f64 / 20.0);
+                let (sin, cos) = angle.sin_cos();
+                rect.center() + radius * egui::vec2(cos as f32, sin as f32)
+            })
+            .collect();
+        let stroke = egui::Stroke::new(3.0, ui.visuals().strong_text_color());
+        ui.painter().add(egui::Shape::line(points, stroke));
+    }
+    response
+}
+
+// Format a size with one decimal, e.g. "512 B", "1.5 KB", "4.2 MB".
+// Binary units: 1 KB is 1024 bytes, like the max image size setting.
+pub fn human_bytes(n: usize) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+    let mut size = n as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
     }
+    format!("{:.1} {}", size, UNITS[unit])
 }
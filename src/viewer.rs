@@ -0,0 +1,120 @@
+use crate::texture::TextureImage;
+use eframe::egui;
+
+/// Zoom range of an image window.
+pub const WINDOW_ZOOM: std::ops::RangeInclusive<f32> = 0.1..=8.0;
+
+/// An image opened in a window of its own, zoomed and panned independently of the main view.
+pub struct ImageWindow {
+    id: u64,
+    title: String,
+    image: TextureImage,
+    zoom: f32,
+    open: bool,
+}
+
+impl ImageWindow {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn image(&self) -> &TextureImage {
+        &self.image
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(*WINDOW_ZOOM.start(), *WINDOW_ZOOM.end());
+    }
+
+    fn show(&mut self, ctx: &egui::Context) {
+        let ppp = ctx.pixels_per_point();
+        let actual = self.image.size_vec2() / ppp;
+        let id = egui::Id::new(("image_window", self.id));
+        let (image, zoom) = (&self.image, &mut self.zoom);
+        egui::Window::new(&self.title)
+            .id(id)
+            .open(&mut self.open)
+            .resizable(true)
+            .default_size(actual.min(egui::vec2(480.0, 360.0)))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Slider::new(zoom, WINDOW_ZOOM).text("zoom"));
+                    if ui.button("100%").clicked() {
+                        *zoom = 1.0;
+                    }
+                });
+                // Its own scroll state, panning one window leaves the others alone.
+                egui::ScrollArea::both()
+                    .id_source(id.with("scroll"))
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        image.show_size(ui, actual * *zoom);
+                    });
+            });
+    }
+}
+
+/// The images opened in windows of their own, shown over the main view until closed.
+///
+/// Windows only hold a (cheap) clone of the image, its texture is freed once
+/// the window is closed and nothing else uses it. Fetching goes on in the main view,
+/// with the same fetcher, while any number of them are open.
+#[derive(Default)]
+pub struct ImageWindows {
+    windows: Vec<ImageWindow>,
+    next_id: u64,
+}
+
+impl ImageWindows {
+    /// Open `image` in a new window titled `title`, at 100%.
+    pub fn open(&mut self, title: impl Into<String>, image: TextureImage) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.windows.push(ImageWindow {
+            id,
+            title: title.into(),
+            image,
+            zoom: 1.0,
+            open: true,
+        });
+        id
+    }
+
+    /// Close the window `id`, nothing happens if it's closed already.
+    pub fn close(&mut self, id: u64) {
+        self.windows.retain(|window| window.id != id);
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut ImageWindow> {
+        self.windows.iter_mut().find(|window| window.id == id)
+    }
+
+    /// The open windows, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &ImageWindow> {
+        self.windows.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Paint every window, the ones closed with their title bar button are dropped.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        for window in &mut self.windows {
+            window.show(ctx);
+        }
+        self.windows.retain(|window| window.open);
+    }
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use eframe::egui;
+use eframe_tokio_app::{
+    archive::{entry_name, write_zip, ArchiveJob, ArchiveState, ManifestEntry, MANIFEST_NAME},
+    utils::Channel,
+    AsyncFetcher,
+};
+use std::{
+    fs::File,
+    io::{Cursor, Read},
+    thread,
+    time::{Duration, Instant},
+};
+use zip::ZipArchive;
+
+// Sorted, the archive's index doesn't keep the order they were written in.
+fn entry_names<R: Read + std::io::Seek>(zip: &ZipArchive<R>) -> Vec<&str> {
+    let mut names: Vec<_> = zip.file_names().collect();
+    names.sort_unstable();
+    names
+}
+
+fn read_entry<R: Read + std::io::Seek>(zip: &mut ZipArchive<R>, name: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    zip.by_name(name).unwrap().read_to_end(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn entries_are_named_after_the_url() {
+    let png = common::png_bytes(2, 2);
+    assert_eq!(
+        entry_name("https://picsum.photos/seed/42/512", &png),
+        "seed-42-512.png"
+    );
+    assert_eq!(
+        entry_name("http://host/images/cat.png?w=100#top", &png),
+        "images-cat.png"
+    );
+    assert_eq!(entry_name("http://host/a b/c", b"not an image"), "a_b-c");
+    assert_eq!(entry_name("http://host/", &png), "image.png");
+}
+
+#[test]
+fn zip_holds_every_image_and_a_manifest() {
+    let png = common::png_bytes(4, 4);
+    let images = vec![
+        ("http://host/seed/1/64".to_owned(), Ok(png.clone())),
+        ("http://other/seed/1/64".to_owned(), Ok(png.clone())),
+        (
+            "http://host/seed/2/64".to_owned(),
+            Err("Not found".to_owned()),
+        ),
+    ];
+    let mut buffer = Cursor::new(Vec::new());
+    let manifest = write_zip(&mut buffer, images).unwrap();
+    assert_eq!(
+        manifest[1],
+        ManifestEntry {
+            url: "http://other/seed/1/64".into(),
+            file: Some("seed-1-64-2.png".into()),
+            bytes: png.len(),
+            error: None,
+        }
+    );
+    assert_eq!(manifest[2].file, None);
+
+    let mut zip = ZipArchive::new(buffer).unwrap();
+    assert_eq!(
+        entry_names(&zip),
+        [MANIFEST_NAME, "seed-1-64-2.png", "seed-1-64.png"]
+    );
+    assert_eq!(read_entry(&mut zip, "seed-1-64-2.png"), png);
+    let json: serde_json::Value =
+        serde_json::from_slice(&read_entry(&mut zip, MANIFEST_NAME)).unwrap();
+    assert_eq!(json[0]["file"], "seed-1-64.png");
+    assert_eq!(json[2]["url"], "http://host/seed/2/64");
+    assert_eq!(json[2]["error"], "Not found");
+}
+
+#[test]
+fn archive_job_exports_what_it_could_fetch() {
+    let png = common::png_bytes(4, 4);
+    let len = png.len();
+    let url = common::serve_many(move |_, stream| common::write_png(stream, &png));
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("images.zip");
+
+    let fetcher = AsyncFetcher::new(&egui::Context::default());
+    let job = ArchiveJob::new();
+    let urls = vec![format!("{}photo", url), "http://127.0.0.1:1/missing".into()];
+    job.spawn(&fetcher, urls, path.clone());
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut received = 0;
+    let manifest = loop {
+        assert!(Instant::now() < deadline, "export did not finish in time");
+        match job.poll() {
+            ArchiveState::Running(Some(Channel::Data(bytes))) => received += bytes,
+            ArchiveState::Done(result) => break result.unwrap(),
+            _ => thread::sleep(Duration::from_millis(5)),
+        }
+    };
+    assert_eq!(received, len);
+    assert_eq!(manifest.len(), 2);
+    assert!(manifest[1].error.is_some());
+
+    let mut zip = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(entry_names(&zip), [MANIFEST_NAME, "photo.png"]);
+    assert_eq!(read_entry(&mut zip, "photo.png").len(), len);
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use eframe::egui;
+use eframe_tokio_app::{
+    batch::{BatchJob, BatchState},
+    AsyncFetcher, FetchError,
+};
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+fn poll_until_done(batch: &BatchJob) -> BatchState {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        match batch.poll() {
+            state @ BatchState::Done(_) => return state,
+            _ => thread::sleep(Duration::from_millis(5)),
+        }
+    }
+    panic!("batch did not finish in time");
+}
+
+#[test]
+fn batch_reports_every_image() {
+    let png = common::png_bytes(4, 4);
+    let len = png.len();
+    let url = common::serve_many(move |_, stream| common::write_png(stream, &png));
+    let fetcher = AsyncFetcher::new(&egui::Context::default());
+    let batch = BatchJob::new();
+    let urls = vec![url.clone(), "http://127.0.0.1:1/".into(), url];
+    batch.spawn(&fetcher, urls);
+    let items = match poll_until_done(&batch) {
+        BatchState::Done(Ok(items)) => items,
+        _ => panic!("expected batch results"),
+    };
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].result.as_ref().ok(), Some(&len));
+    assert!(items[1].result.is_err());
+    assert_eq!(items[2].result.as_ref().ok(), Some(&len));
+}
+
+#[test]
+fn validation_checks_status_and_type_without_downloading() {
+    let png = common::png_bytes(4, 4);
+    let len = png.len();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let url = {
+        let requests = requests.clone();
+        common::serve_many(move |request, stream| {
+            let line = request.lines().next().unwrap().to_owned();
+            let head = line.starts_with("HEAD");
+            requests.lock().unwrap().push(line.clone());
+            let path = line.split(' ').nth(1).unwrap();
+            match (path, head) {
+                ("/image.png", true) => common::write_head(
+                    stream,
+                    "200 OK",
+                    &[
+                        ("Content-Type", "image/png".into()),
+                        ("Content-Length", len.to_string()),
+                    ],
+                ),
+                ("/image.png", false) => common::write_png(stream, &png),
+                // No HEAD, and a generic type: the first bytes tell.
+                ("/no-head", true) => common::write_head(stream, "405 Method Not Allowed", &[]),
+                ("/no-head", false) => {
+                    assert!(request.to_ascii_lowercase().contains("range: bytes=0-"));
+                    common::write_head(
+                        stream,
+                        "206 Partial Content",
+                        &[
+                            ("Content-Type", "application/octet-stream".into()),
+                            ("Content-Range", format!("bytes 0-15/{}", len)),
+                            ("Content-Length", "16".into()),
+                        ],
+                    );
+                    stream.write_all(&png[..16]).unwrap();
+                }
+                ("/page", _) => {
+                    common::write_head(stream, "200 OK", &[("Content-Type", "text/html".into())])
+                }
+                _ => common::write_head(stream, "404 Not Found", &[]),
+            }
+        })
+    };
+    let fetcher = AsyncFetcher::new(&egui::Context::default());
+    let batch = BatchJob::new();
+    let urls = ["image.png", "missing.png", "no-head", "page"]
+        .iter()
+        .map(|path| format!("{}{}", url, path))
+        .collect();
+    batch.spawn_validate(&fetcher, urls);
+    let items = match poll_until_done(&batch) {
+        BatchState::Done(Ok(items)) => items,
+        _ => panic!("expected batch results"),
+    };
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0].result.as_ref().ok(), Some(&len));
+    assert!(matches!(items[1].result, Err(FetchError::NotFound)));
+    assert_eq!(items[2].result.as_ref().ok(), Some(&len));
+    assert!(matches!(
+        items[3].result,
+        Err(FetchError::UnsupportedContentType(_))
+    ));
+
+    // The image itself was never downloaded.
+    let requests = requests.lock().unwrap();
+    assert!(!requests.contains(&"GET /image.png HTTP/1.1".to_owned()));
+    assert!(requests.contains(&"GET /no-head HTTP/1.1".to_owned()));
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use std::process::Command;
+
+fn fetch_cli(url: &str, out: &std::path::Path) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_eframe_tokio_app"))
+        .args(["--fetch", url, "--out"])
+        .arg(out)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn cli_saves_the_response_bytes() {
+    let png = common::png_bytes(3, 3);
+    let body = png.clone();
+    let url = common::serve_once(move |_, stream| common::write_png(stream, &body));
+    let out = std::env::temp_dir().join(format!("eframe_tokio_cli_{}.png", std::process::id()));
+
+    let output = fetch_cli(&url, &out);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(std::fs::read(&out).unwrap(), png);
+    std::fs::remove_file(&out).unwrap();
+}
+
+#[test]
+fn cli_fails_on_http_errors() {
+    let url = common::serve_once(|_, stream| common::write_head(stream, "404 Not Found", &[]));
+    let out = std::env::temp_dir().join(format!("eframe_tokio_cli_{}.404", std::process::id()));
+
+    let output = fetch_cli(&url, &out);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No image found"));
+    assert!(!out.exists());
+}
@@ -0,0 +1,55 @@
+use eframe_tokio_app::clipboard::{ClipboardContent, Paste};
+
+fn paste_text(text: &str) -> Paste {
+    Paste::from_content(ClipboardContent::Text(text.to_owned()))
+}
+
+#[test]
+fn urls_are_fetched_and_images_shown() {
+    match paste_text("  https://picsum.photos/id/1/300\n") {
+        Paste::Url(url) => assert_eq!(url, "https://picsum.photos/id/1/300"),
+        _ => panic!("a URL should be fetched"),
+    }
+    assert!(matches!(
+        paste_text("data:image/png;base64,AAAA"),
+        Paste::Url(_)
+    ));
+
+    let rgba = [255, 0, 0, 255, 0, 0, 255, 128].to_vec();
+    match Paste::from_content(ClipboardContent::Image { size: [2, 1], rgba }) {
+        Paste::Image(pixels) => {
+            assert_eq!(pixels.size, [2, 1]);
+            assert_eq!(pixels.pixels[0].to_array(), [255, 0, 0, 255]);
+        }
+        _ => panic!("an image should be shown"),
+    }
+}
+
+#[test]
+fn anything_else_is_unsupported() {
+    for text in [
+        "",
+        "   ",
+        "just some words",
+        "ftp://example.com/a.png",
+        "file:///a.png",
+    ] {
+        assert!(
+            matches!(paste_text(text), Paste::Unsupported(_)),
+            "{:?}",
+            text
+        );
+    }
+    assert!(matches!(
+        Paste::from_content(ClipboardContent::Empty),
+        Paste::Unsupported("The clipboard is empty.")
+    ));
+    let truncated = ClipboardContent::Image {
+        size: [2, 2],
+        rgba: vec![0; 4],
+    };
+    assert!(matches!(
+        Paste::from_content(truncated),
+        Paste::Unsupported(_)
+    ));
+}
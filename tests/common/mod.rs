@@ -0,0 +1,147 @@
+#![allow(dead_code)]
+use image::{ImageOutputFormat, Rgba, RgbaImage};
+use std::{
+    io::{BufRead, BufReader, Cursor, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+/// Encode a solid `width`x`height` PNG.
+pub fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    RgbaImage::from_pixel(width, height, Rgba([255, 0, 0, 255]))
+        .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+        .unwrap();
+    bytes
+}
+
+/// Serve a single connection on a random local port and return its URL.
+///
+/// `respond` receives the raw request head and writes the whole response by itself.
+pub fn serve_once(respond: impl FnOnce(String, &mut TcpStream) + Send + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let request = read_request_head(&stream);
+        respond(request, &mut stream);
+    });
+    url
+}
+
+/// Read the request head, so closing the connection after answering doesn't reset it.
+pub fn read_request_head(stream: &TcpStream) -> String {
+    let mut reader = BufReader::new(stream);
+    let mut head = String::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+            break;
+        }
+        head.push_str(&line);
+    }
+    head
+}
+
+/// Write a response head with the given status line and extra headers.
+pub fn write_head(stream: &mut TcpStream, status: &str, headers: &[(&str, String)]) {
+    let mut head = format!("HTTP/1.1 {}\r\nConnection: close\r\n", status);
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).unwrap();
+}
+
+/// Serve every incoming connection on its own thread, until the test ends.
+pub fn serve_many(respond: impl Fn(String, &mut TcpStream) + Send + Sync + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let respond = std::sync::Arc::new(respond);
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let respond = respond.clone();
+            thread::spawn(move || {
+                let request = read_request_head(&stream);
+                respond(request, &mut stream);
+            });
+        }
+    });
+    url
+}
+
+/// Respond with a complete PNG body.
+pub fn write_png(stream: &mut TcpStream, png: &[u8]) {
+    let headers = [
+        ("Content-Type", "image/png".to_string()),
+        ("Content-Length", png.len().to_string()),
+    ];
+    write_head(stream, "200 OK", &headers);
+    stream.write_all(png).unwrap();
+}
+
+/// Serve `png` to every request, keeping connections alive, and count accepted connections.
+pub fn serve_keep_alive(png: Vec<u8>) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+    use std::sync::{atomic::Ordering, Arc};
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let connections = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = connections.clone();
+    let png = Arc::new(png);
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let png = png.clone();
+            thread::spawn(move || {
+                // Keep answering on the same connection until the client closes it.
+                while !read_request_head(&stream).is_empty() {
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: {}\r\n\r\n",
+                        png.len()
+                    );
+                    if stream.write_all(head.as_bytes()).is_err() || stream.write_all(&png).is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    (url, connections)
+}
+
+/// Serve `png` over HTTPS with a self-signed certificate, to every connection.
+///
+/// Failed handshakes (the client rejecting the certificate) are ignored.
+pub fn serve_self_signed(png: Vec<u8>) -> String {
+    let cert = include_bytes!("../fixtures/self_signed.crt");
+    let key = include_bytes!("../fixtures/self_signed.key");
+    let identity = native_tls::Identity::from_pkcs8(cert, key).unwrap();
+    let acceptor = std::sync::Arc::new(native_tls::TlsAcceptor::new(identity).unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("https://{}/", listener.local_addr().unwrap());
+    let png = std::sync::Arc::new(png);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let (acceptor, png) = (acceptor.clone(), png.clone());
+            thread::spawn(move || {
+                let mut stream = match acceptor.accept(stream) {
+                    Ok(stream) => stream,
+                    Err(_) => return,
+                };
+                let mut reader = BufReader::new(&mut stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                    line.clear();
+                }
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Type: image/png\r\nContent-Length: {}\r\n\r\n",
+                    png.len()
+                );
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(&png);
+            });
+        }
+    });
+    url
+}
@@ -0,0 +1,61 @@
+use eframe_tokio_app::{
+    data_uri::{is_data_uri, DataUri},
+    decode::ImageFormat,
+    FetchError,
+};
+
+// A 1x1 PNG.
+const PNG_BASE64: &str =
+    "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8z8BQDwAEhQGAhKmMIQAAAABJRU5ErkJggg==";
+
+#[test]
+fn base64_png_decodes_to_an_image() {
+    let uri = format!("data:image/png;base64,{}", PNG_BASE64);
+    assert!(is_data_uri(&uri));
+    assert!(is_data_uri("DATA:image/png;base64,AAAA"));
+    assert!(!is_data_uri("https://example.com/data:image/png"));
+
+    let data = DataUri::parse(&uri).unwrap();
+    assert_eq!(data.media_type, "image/png");
+    assert_eq!(data.name(), "data:image/png");
+    let format = data.format().unwrap();
+    assert_eq!(format, ImageFormat::Png);
+    assert_eq!(format.decode(&data.bytes, None).unwrap().size, [1, 1]);
+
+    // Line breaks, missing padding and a generic media type are all fine, the format is sniffed.
+    let (head, tail) = PNG_BASE64.trim_end_matches('=').split_at(20);
+    let uri = format!("data:application/octet-stream;base64,{}\n{}", head, tail);
+    let sniffed = DataUri::parse(&uri).unwrap();
+    assert_eq!(sniffed.bytes, data.bytes);
+    assert_eq!(sniffed.format(), Some(ImageFormat::Png));
+}
+
+#[test]
+fn plain_data_is_percent_decoded() {
+    let data = DataUri::parse("data:,hello%20world").unwrap();
+    assert_eq!(data.media_type, "text/plain");
+    assert_eq!(data.bytes, b"hello world");
+    assert_eq!(data.format(), None);
+
+    let svg = DataUri::parse("data:image/svg+xml;utf8,%3Csvg%3E%3C/svg%3E").unwrap();
+    assert_eq!(svg.bytes, b"<svg></svg>");
+    assert_eq!(svg.format(), Some(ImageFormat::Svg));
+}
+
+#[test]
+fn malformed_uris_say_what_is_wrong() {
+    let reason = |uri: &str| match DataUri::parse(uri) {
+        Err(FetchError::InvalidDataUri(reason)) => reason,
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("{} shouldn't parse", uri),
+    };
+    assert_eq!(
+        reason("data:image/png;base64"),
+        "missing the ',' before the data"
+    );
+    assert_eq!(reason("data:image/png;base64,"), "no data");
+    assert_eq!(reason("data:,50%"), "malformed percent escape");
+    assert_eq!(reason("data:,%zz"), "malformed percent escape");
+    assert!(reason("data:image/png;base64,not*base64").starts_with("bad base64 data"));
+    assert_eq!(reason("https://example.com"), "missing the data: scheme");
+}
@@ -0,0 +1,277 @@
+use eframe::egui;
+use eframe_tokio_app::{
+    animation::Animation,
+    decode::{
+        content_hash, decode_gif_frames, decode_thumbnail, decode_webp_frames, encode, thumbnail,
+        ImageFormat,
+    },
+    FetchError,
+};
+use std::time::Duration;
+
+const TINY_SVG: &[u8] = br#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="2">
+<rect width="4" height="2" fill="red"/></svg>"#;
+
+#[test]
+fn content_type_dispatch() {
+    assert_eq!(
+        ImageFormat::from_content_type("image/jpeg"),
+        Some(ImageFormat::Jpeg)
+    );
+    assert_eq!(
+        ImageFormat::from_content_type("image/png"),
+        Some(ImageFormat::Png)
+    );
+    assert_eq!(
+        ImageFormat::from_content_type("image/svg+xml; charset=utf-8"),
+        Some(ImageFormat::Svg)
+    );
+    assert_eq!(ImageFormat::from_content_type("text/html"), None);
+}
+
+#[test]
+fn tiny_svg_decodes() {
+    let image = ImageFormat::Svg.decode(TINY_SVG, None).unwrap();
+    assert_eq!(image.size, [4, 2]);
+    // Rasterized to fit in the requested size, aspect ratio kept.
+    let image = ImageFormat::Svg.decode(TINY_SVG, Some([40, 40])).unwrap();
+    assert_eq!(image.size, [40, 20]);
+}
+
+#[test]
+fn malformed_svg_is_an_error() {
+    assert!(ImageFormat::Svg.decode(b"<svg", None).is_err());
+}
+
+#[test]
+fn truncated_png_is_a_decode_error() {
+    let mut png = Vec::new();
+    image::RgbaImage::new(8, 8)
+        .write_to(
+            &mut std::io::Cursor::new(&mut png),
+            image::ImageOutputFormat::Png,
+        )
+        .unwrap();
+    png.truncate(png.len() / 2);
+
+    match ImageFormat::Png.decode(&png, None) {
+        Err(FetchError::Decode { bytes, .. }) => assert_eq!(bytes, png.len()),
+        _ => panic!("expected a decode error"),
+    }
+}
+
+#[test]
+fn identical_bytes_hash_equal() {
+    let bytes = TINY_SVG.to_vec();
+    assert_eq!(content_hash(TINY_SVG), content_hash(&bytes));
+    let mut other = bytes.clone();
+    *other.last_mut().unwrap() ^= 1;
+    assert_ne!(content_hash(&bytes), content_hash(&other));
+}
+
+#[test]
+fn animated_gif_decodes_every_frame() {
+    use image::{codecs::gif::GifEncoder, Delay, Frame, Rgba, RgbaImage};
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut bytes);
+        for color in [[255, 0, 0, 255], [0, 0, 255, 255]] {
+            let buffer = RgbaImage::from_pixel(3, 2, Rgba(color));
+            let delay = Delay::from_numer_denom_ms(50, 1);
+            encoder
+                .encode_frame(Frame::from_parts(buffer, 0, 0, delay))
+                .unwrap();
+        }
+    }
+    let frames = decode_gif_frames(&bytes, Animation::MAX_FRAMES, Animation::MAX_BYTES).unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].0.size, [3, 2]);
+    assert_eq!(frames[1].1, Duration::from_millis(50));
+    // Still the first frame only with the plain decoder.
+    assert_eq!(ImageFormat::Gif.decode(&bytes, None).unwrap().size, [3, 2]);
+
+    let (mut animation, _) = Animation::decode_gif(&egui::Context::default(), "test", &bytes)
+        .unwrap()
+        .unwrap();
+    assert_eq!(animation.advance(0.0), Some(Duration::from_millis(50)));
+    animation.advance(0.06);
+    assert_eq!(animation.current(), 1);
+    animation.advance(0.11);
+    assert_eq!(animation.current(), 0);
+    animation.step(false);
+    assert_eq!(animation.current(), 1);
+    assert!(!animation.is_playing());
+    assert_eq!(animation.advance(1.0), None);
+}
+
+// A still lossless WebP of `color`.
+fn still_webp(width: u32, height: u32, color: [u8; 4]) -> Vec<u8> {
+    use image::{codecs::webp::WebPEncoder, ColorType, Rgba, RgbaImage};
+    let buffer = RgbaImage::from_pixel(width, height, Rgba(color));
+    let mut bytes = Vec::new();
+    WebPEncoder::new_lossless(&mut bytes)
+        .encode(buffer.as_raw(), width, height, ColorType::Rgba8)
+        .unwrap();
+    bytes
+}
+
+// A RIFF chunk, padded to an even size.
+fn webp_chunk(fourcc: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut chunk = fourcc.to_vec();
+    chunk.extend((payload.len() as u32).to_le_bytes());
+    chunk.extend(payload);
+    if payload.len() % 2 == 1 {
+        chunk.push(0);
+    }
+    chunk
+}
+
+fn u24(n: u32) -> [u8; 3] {
+    let [a, b, c, _] = n.to_le_bytes();
+    [a, b, c]
+}
+
+// An animated WebP of `width`x`height` frames, one per color, each shown `delay_ms`.
+// The encoder only writes still images, their VP8L chunks are wrapped into ANMF frames.
+fn animated_webp(width: u32, height: u32, colors: &[[u8; 4]], delay_ms: u32) -> Vec<u8> {
+    let mut vp8x = vec![0x10 | 0x02, 0, 0, 0];
+    vp8x.extend(u24(width - 1));
+    vp8x.extend(u24(height - 1));
+    let mut body = b"WEBP".to_vec();
+    body.extend(webp_chunk(b"VP8X", &vp8x));
+    body.extend(webp_chunk(b"ANIM", &[0, 0, 0, 0, 0, 0]));
+    for &color in colors {
+        // Past "RIFF", its size and "WEBP", the still image is a single VP8L chunk.
+        let still = still_webp(width, height, color);
+        let mut anmf = Vec::new();
+        anmf.extend(u24(0));
+        anmf.extend(u24(0));
+        anmf.extend(u24(width - 1));
+        anmf.extend(u24(height - 1));
+        anmf.extend(u24(delay_ms));
+        // No blending, so each frame is exactly its color.
+        anmf.push(0b10);
+        anmf.extend(&still[12..]);
+        body.extend(webp_chunk(b"ANMF", &anmf));
+    }
+    let mut bytes = b"RIFF".to_vec();
+    bytes.extend((body.len() as u32).to_le_bytes());
+    bytes.extend(body);
+    bytes
+}
+
+#[test]
+fn animated_webp_decodes_every_frame_within_the_caps() {
+    let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]];
+    let bytes = animated_webp(3, 2, &colors, 40);
+    assert_eq!(ImageFormat::sniff(&bytes), Some(ImageFormat::Webp));
+    assert!(ImageFormat::Webp.can_animate());
+
+    let frames = decode_webp_frames(&bytes, Animation::MAX_FRAMES, Animation::MAX_BYTES).unwrap();
+    assert_eq!(frames.len(), 3);
+    for ((pixels, delay), color) in frames.iter().zip(colors) {
+        assert_eq!(pixels.size, [3, 2]);
+        assert_eq!(pixels.pixels[0].to_array(), color);
+        assert_eq!(*delay, Duration::from_millis(40));
+    }
+
+    // Cut after `max_frames`, or once the pixels outgrow `max_bytes`.
+    let frames = decode_webp_frames(&bytes, 2, Animation::MAX_BYTES).unwrap();
+    assert_eq!(frames.len(), 2);
+    let frame_bytes = 3 * 2 * 4;
+    let frames = decode_webp_frames(&bytes, Animation::MAX_FRAMES, 2 * frame_bytes).unwrap();
+    assert_eq!(frames.len(), 2);
+    // The first frame is kept whatever its size.
+    let frames = decode_webp_frames(&bytes, Animation::MAX_FRAMES, 1).unwrap();
+    assert_eq!(frames.len(), 1);
+
+    let ctx = egui::Context::default();
+    let (animation, first) = Animation::decode(&ctx, ImageFormat::Webp, "test", &bytes)
+        .unwrap()
+        .unwrap();
+    assert_eq!(animation.len(), 3);
+    assert_eq!(first.pixels[0].to_array(), colors[0]);
+}
+
+#[test]
+fn still_webp_stays_a_single_image() {
+    let bytes = still_webp(3, 2, [255, 0, 0, 255]);
+    let frames = decode_webp_frames(&bytes, Animation::MAX_FRAMES, Animation::MAX_BYTES).unwrap();
+    assert!(frames.is_empty());
+    let ctx = egui::Context::default();
+    assert!(Animation::decode(&ctx, ImageFormat::Webp, "test", &bytes)
+        .unwrap()
+        .is_none());
+    assert_eq!(ImageFormat::Webp.decode(&bytes, None).unwrap().size, [3, 2]);
+    // Formats without frames never animate.
+    assert!(Animation::decode(&ctx, ImageFormat::Png, "test", &bytes)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn formats_are_sniffed_from_magic_bytes() {
+    let png = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', 0];
+    assert_eq!(ImageFormat::sniff(&png), Some(ImageFormat::Png));
+    assert_eq!(
+        ImageFormat::sniff(&[0xff, 0xd8, 0xff, 0xe0]),
+        Some(ImageFormat::Jpeg)
+    );
+    assert_eq!(ImageFormat::sniff(b"GIF89a..."), Some(ImageFormat::Gif));
+    assert_eq!(
+        ImageFormat::sniff(b"RIFF\0\0\0\0WEBPVP8 "),
+        Some(ImageFormat::Webp)
+    );
+    assert_eq!(ImageFormat::sniff(TINY_SVG), Some(ImageFormat::Svg));
+    assert_eq!(
+        ImageFormat::sniff(b"<?xml version=\"1.0\"?>\n<svg></svg>"),
+        Some(ImageFormat::Svg)
+    );
+    assert_eq!(ImageFormat::sniff(b"<html></html>"), None);
+    assert_eq!(ImageFormat::sniff(b""), None);
+}
+
+#[test]
+fn png_converts_to_jpeg_and_back() {
+    let pixels = egui::ColorImage::new([8, 6], egui::Color32::from_rgb(200, 40, 40));
+    let png = encode(&pixels, ImageFormat::Png, 90).unwrap();
+    let decoded = ImageFormat::Png.decode(&png, None).unwrap();
+    assert!(decoded == pixels);
+
+    let jpeg = encode(&decoded, ImageFormat::Jpeg, 90).unwrap();
+    assert_eq!(ImageFormat::sniff(&jpeg), Some(ImageFormat::Jpeg));
+    let decoded = ImageFormat::Jpeg.decode(&jpeg, None).unwrap();
+    assert_eq!(decoded.size, [8, 6]);
+    // Lossy, but close.
+    let [r, _, _, _] = decoded.pixels[0].to_array();
+    assert!((190..=210).contains(&r));
+
+    let png = encode(&decoded, ImageFormat::Png, 90).unwrap();
+    assert!(ImageFormat::Png.decode(&png, None).unwrap() == decoded);
+}
+
+#[test]
+fn webp_is_lossless_and_svg_cant_be_encoded() {
+    let pixels = egui::ColorImage::new([3, 3], egui::Color32::from_rgb(10, 120, 250));
+    let webp = encode(&pixels, ImageFormat::Webp, 90).unwrap();
+    assert_eq!(ImageFormat::sniff(&webp), Some(ImageFormat::Webp));
+    assert!(ImageFormat::Webp.decode(&webp, None).unwrap() == pixels);
+    assert!(encode(&pixels, ImageFormat::Svg, 90).is_err());
+}
+
+#[test]
+fn thumbnails_fit_the_max_and_keep_the_aspect_ratio() {
+    let wide = egui::ColorImage::new([200, 100], egui::Color32::RED);
+    let png = encode(&wide, ImageFormat::Png, 90).unwrap();
+    assert_eq!(decode_thumbnail(&png, 64).unwrap().size, [64, 32]);
+
+    let tall = egui::ColorImage::new([30, 120], egui::Color32::RED);
+    assert_eq!(thumbnail(&tall, 64).size, [16, 64]);
+    // Never upscaled.
+    let small = egui::ColorImage::new([10, 5], egui::Color32::RED);
+    assert_eq!(thumbnail(&small, 64).size, [10, 5]);
+
+    let svg = decode_thumbnail(TINY_SVG, 64).unwrap();
+    assert_eq!(svg.size, [64, 32]);
+    assert!(decode_thumbnail(b"not an image", 64).is_err());
+}
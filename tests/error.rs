@@ -0,0 +1,102 @@
+mod common;
+
+use eframe_tokio_app::{error::classify_error, CancelReason, FetchError};
+use std::{io::Write, net::TcpListener, thread, time::Duration};
+
+// The error of a GET to `url` with `client`, the body is read as well.
+fn request_error(client: &reqwest::Client, url: &str) -> reqwest::Error {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        match client.get(url).send().await {
+            Ok(response) => response.bytes().await.unwrap_err(),
+            Err(e) => e,
+        }
+    })
+}
+
+#[test]
+fn request_errors_map_to_their_category() {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+
+    let (error, hint) = classify_error(&request_error(&client, "http://nonexistent.invalid/"));
+    assert!(matches!(error, FetchError::Dns(_)), "{:?}", error);
+    assert_eq!(error.hint(), Some(hint));
+
+    // Nothing listens on the port once the listener is gone.
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let url = format!("http://127.0.0.1:{}/", port);
+    let (error, _) = classify_error(&request_error(&client, &url));
+    assert!(matches!(error, FetchError::Connect(_)), "{:?}", error);
+
+    let url = common::serve_self_signed(common::png_bytes(2, 2));
+    let (error, hint) = classify_error(&request_error(&client, &url));
+    assert!(matches!(error, FetchError::Tls(_)), "{:?}", error);
+    assert!(hint.contains("certificate"));
+
+    // Accepted, but never answered.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        common::read_request_head(&stream);
+        thread::sleep(Duration::from_secs(2));
+    });
+    let (error, _) = classify_error(&request_error(&client, &url));
+    assert!(
+        matches!(error, FetchError::NetworkTimeout(_)),
+        "{:?}",
+        error
+    );
+
+    // Closed halfway through the announced length.
+    let url = common::serve_once(|_, stream| {
+        common::write_head(stream, "200 OK", &[("Content-Length", "100".to_string())]);
+        stream.write_all(&[0; 10]).unwrap();
+    });
+    let (error, _) = classify_error(&request_error(&client, &url));
+    assert!(matches!(error, FetchError::Body(_)), "{:?}", error);
+    assert!(error.is_retryable());
+}
+
+#[test]
+fn only_network_errors_have_hints() {
+    for error in [
+        FetchError::Network(String::new()),
+        FetchError::Tls(String::new()),
+        FetchError::Dns(String::new()),
+        FetchError::Connect(String::new()),
+        FetchError::NetworkTimeout(String::new()),
+        FetchError::Body(String::new()),
+    ] {
+        assert!(error.hint().is_some(), "{:?}", error);
+    }
+    assert_eq!(FetchError::NotFound.hint(), None);
+    assert_eq!(FetchError::from(CancelReason::UserRequested).hint(), None);
+}
+
+#[test]
+fn only_timeouts_are_cancels_worth_reporting() {
+    let deadline = Duration::from_secs(30);
+    for reason in [
+        CancelReason::UserRequested,
+        CancelReason::Shutdown,
+        CancelReason::Superseded,
+    ] {
+        let error = FetchError::from(reason);
+        assert!(error.is_deliberate_cancel(), "{:?}", reason);
+        assert!(!error.is_retryable(), "{:?}", reason);
+        assert_eq!(error.to_string(), "Fetching image canceled.");
+    }
+    let timeout = FetchError::from(CancelReason::Timeout(deadline));
+    assert!(!timeout.is_deliberate_cancel());
+    assert!(timeout.is_retryable());
+    assert_eq!(timeout.to_string(), "Fetching image took longer than 30s");
+    assert!(!FetchError::NotFound.is_deliberate_cancel());
+}
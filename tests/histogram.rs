@@ -0,0 +1,51 @@
+use eframe::egui::{Color32, ColorImage};
+use eframe_tokio_app::histogram::Histogram;
+
+#[test]
+fn every_channel_counts_every_pixel() {
+    let pixels = (0..64 * 48)
+        .map(|i| Color32::from_rgb((i % 256) as u8, (i / 7 % 256) as u8, (i * 13 % 256) as u8))
+        .collect();
+    let image = ColorImage {
+        size: [64, 48],
+        pixels,
+    };
+    let histogram = Histogram::of(&image);
+    for channel in [
+        &histogram.red,
+        &histogram.green,
+        &histogram.blue,
+        &histogram.luma,
+    ] {
+        assert_eq!(channel.len(), 256);
+        assert_eq!(channel.iter().sum::<u32>(), 64 * 48);
+    }
+}
+
+#[test]
+fn values_land_in_their_bins() {
+    let image = ColorImage {
+        size: [2, 2],
+        pixels: vec![
+            Color32::RED,
+            Color32::GREEN,
+            Color32::from_rgb(0, 0, 255),
+            Color32::WHITE,
+        ],
+    };
+    let histogram = Histogram::of(&image);
+    assert_eq!((histogram.red[255], histogram.red[0]), (2, 2));
+    assert_eq!((histogram.green[255], histogram.green[0]), (2, 2));
+    assert_eq!((histogram.blue[255], histogram.blue[0]), (2, 2));
+    // Green weighs the most in the luma, blue the least.
+    for luma in [54, 182, 18, 255] {
+        assert_eq!(histogram.luma[luma], 1, "luma {}", luma);
+    }
+
+    // Translucent pixels count with their own color, not darkened by the alpha.
+    let translucent = ColorImage::from_rgba_unmultiplied([1, 1], &[200, 100, 50, 128]);
+    let histogram = Histogram::of(&translucent);
+    // Color32 is stored premultiplied, the round trip may be off by one.
+    let red = histogram.red.iter().position(|&count| count == 1).unwrap();
+    assert!(red.abs_diff(200) <= 1, "red {}", red);
+}
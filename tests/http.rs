@@ -0,0 +1,35 @@
+use eframe_tokio_app::http::{header_pairs, REDACTED};
+use reqwest::header::{HeaderMap, HeaderValue};
+
+#[test]
+fn credentials_are_redacted_on_request() {
+    let mut headers = HeaderMap::new();
+    headers.insert("content-type", HeaderValue::from_static("image/png"));
+    headers.insert("set-cookie", HeaderValue::from_static("session=secret"));
+    let mut token = HeaderValue::from_static("token");
+    token.set_sensitive(true);
+    headers.insert("x-token", token);
+
+    let pairs = |redact| {
+        header_pairs(&headers, redact)
+            .into_iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        pairs(false),
+        [
+            "content-type: image/png".to_owned(),
+            "set-cookie: session=secret".to_owned(),
+            format!("x-token: {}", REDACTED),
+        ]
+    );
+    assert_eq!(
+        pairs(true),
+        [
+            "content-type: image/png".to_owned(),
+            format!("set-cookie: {}", REDACTED),
+            format!("x-token: {}", REDACTED),
+        ]
+    );
+}
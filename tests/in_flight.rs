@@ -0,0 +1,176 @@
+use eframe::egui;
+use eframe_tokio_app::{
+    in_flight::{Follower, InFlight, Joined, Leader},
+    priority::Priority,
+    texture::TextureImage,
+    utils::{Channel, Container, FetchTiming},
+    CancelReason, FetchError,
+};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use std::{sync::Arc, time::Duration};
+
+const URL: &str = "https://a.test/1.png";
+
+fn lead(joined: Joined) -> Leader {
+    match joined {
+        Joined::Leader(leader) => *leader,
+        Joined::Follower(_) => panic!("expected to lead"),
+    }
+}
+
+fn follow(joined: Joined) -> Follower {
+    match joined {
+        Joined::Follower(follower) => follower,
+        Joined::Leader(_) => panic!("expected to follow"),
+    }
+}
+
+#[test]
+fn followers_get_the_leaders_result() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let ctx = egui::Context::default();
+    let in_flight = Arc::new(InFlight::default());
+    let leader = lead(in_flight.join(URL, None, Priority::User));
+    let mut follower = follow(in_flight.join(URL, None, Priority::User));
+    // Other URLs, or the same one told apart by hash, aren't the same fetch.
+    let other = lead(in_flight.join("https://a.test/2.png", None, Priority::User));
+    let hashed = lead(in_flight.join(URL, Some(7), Priority::User));
+    assert_eq!(in_flight.len(), 3);
+    drop((other, hashed));
+
+    let pixels = egui::ColorImage::new([4, 3], egui::Color32::RED);
+    let image = TextureImage::from_color_image(&ctx, "1.png", pixels.clone());
+    leader.finish(&Ok(Container::Image(image, pixels, 42)));
+    assert!(in_flight.is_empty());
+    match rt.block_on(follower.result()) {
+        Some((_, Ok(Container::Image(image, pixels, 42)))) => {
+            assert_eq!(image.size(), [4, 3]);
+            assert_eq!(pixels.size, [4, 3]);
+        }
+        _ => panic!("expected the leader's image"),
+    }
+
+    // Errors are shared too, they'd happen again.
+    let leader = lead(in_flight.join(URL, None, Priority::User));
+    let mut follower = follow(in_flight.join(URL, None, Priority::User));
+    leader.finish(&Err(FetchError::NotFound));
+    assert!(matches!(
+        rt.block_on(follower.result()),
+        Some((_, Err(FetchError::NotFound)))
+    ));
+}
+
+#[test]
+fn followers_are_told_what_the_leader_was() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let in_flight = Arc::new(InFlight::default());
+    let leader = lead(in_flight.join(URL, None, Priority::User));
+    let mut follower = follow(in_flight.join(URL, None, Priority::User));
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("image/png"));
+    let timing = FetchTiming {
+        first_byte: Duration::from_millis(1),
+        last_byte: Duration::from_millis(2),
+        decoded: Duration::from_millis(3),
+        http_version: None,
+    };
+    // A mirror starts over, what the failed URL sent doesn't describe the result.
+    for message in [Channel::ImageTotal(1), Channel::Image(1)] {
+        leader.record(&message);
+    }
+    leader.record(&Channel::Mirror("https://b.test/1.png".into()));
+    for message in [
+        Channel::ImageTotal(30),
+        Channel::ImageHeaders(headers),
+        Channel::Image(10),
+        Channel::ImageStalled,
+        Channel::Image(20),
+        Channel::ImageDecoding,
+        Channel::ImageTiming(timing),
+    ] {
+        leader.record(&message);
+    }
+    leader.finish(&Ok(Container::Unchanged));
+
+    let (messages, result) = rt.block_on(follower.result()).unwrap();
+    assert!(matches!(result, Ok(Container::Unchanged)));
+    assert!(matches!(
+        messages[..],
+        [
+            Channel::ImageTotal(30),
+            Channel::ImageHeaders(_),
+            Channel::Image(30),
+            Channel::ImageTiming(_)
+        ]
+    ));
+    if let (Channel::ImageHeaders(headers), Channel::ImageTiming(told)) =
+        (&messages[1], &messages[3])
+    {
+        assert_eq!(headers[CONTENT_TYPE], "image/png");
+        assert_eq!(told.decoded, timing.decoded);
+    }
+}
+
+#[test]
+fn user_fetches_only_follow_what_wont_hold_them_back() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let in_flight = Arc::new(InFlight::default());
+    let background = lead(in_flight.join(URL, None, Priority::Background));
+    // Background fetches follow anything.
+    let mut background_follower = follow(in_flight.join(URL, None, Priority::Background));
+    // Still waiting for the limiter, a user fetch goes on its own and takes the URL over.
+    let user = lead(in_flight.join(URL, None, Priority::User));
+    let mut user_follower = follow(in_flight.join(URL, None, Priority::Background));
+    // The leader it replaced doesn't free the URL of the new one.
+    background.finish(&Err(FetchError::NotFound));
+    assert!(matches!(
+        rt.block_on(background_follower.result()),
+        Some((_, Err(FetchError::NotFound)))
+    ));
+    assert_eq!(in_flight.len(), 1);
+    user.finish(&Ok(Container::Unchanged));
+    assert!(matches!(
+        rt.block_on(user_follower.result()),
+        Some((_, Ok(Container::Unchanged)))
+    ));
+    assert!(in_flight.is_empty());
+
+    // Once it's through the limiter, it's no slower to follow.
+    let background = lead(in_flight.join(URL, None, Priority::Background));
+    background.set_running();
+    let _follower = follow(in_flight.join(URL, None, Priority::User));
+}
+
+#[test]
+fn followers_fetch_on_their_own_without_a_result() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let in_flight = Arc::new(InFlight::default());
+
+    // Canceled.
+    let leader = lead(in_flight.join(URL, None, Priority::User));
+    let mut follower = follow(in_flight.join(URL, None, Priority::User));
+    leader.finish(&Err(CancelReason::UserRequested.into()));
+    assert!(rt.block_on(follower.result()).is_none());
+
+    // Dropped without finishing, e.g. on shutdown.
+    let leader = lead(in_flight.join(URL, None, Priority::User));
+    let mut follower = follow(in_flight.join(URL, None, Priority::User));
+    drop(leader);
+    assert!(rt.block_on(follower.result()).is_none());
+    assert!(in_flight.is_empty());
+
+    // Timeouts are failures, they're shared.
+    let leader = lead(in_flight.join(URL, None, Priority::User));
+    let mut follower = follow(in_flight.join(URL, None, Priority::User));
+    let deadline = Duration::from_secs(1);
+    leader.finish(&Err(CancelReason::Timeout(deadline).into()));
+    assert!(matches!(
+        rt.block_on(follower.result()),
+        Some((
+            _,
+            Err(FetchError::Canceled {
+                reason: CancelReason::Timeout(_)
+            })
+        ))
+    ));
+}
@@ -0,0 +1,14 @@
+use eframe_tokio_app::job::catch_panic;
+
+#[test]
+fn catch_panic_turns_a_panic_into_its_message() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    assert_eq!(rt.block_on(catch_panic(async { 42 })), Ok(42));
+    let result = rt.block_on(catch_panic(async {
+        tokio::task::yield_now().await;
+        panic!("boom {}", 1);
+    }));
+    assert_eq!(result, Err::<(), _>("boom 1".to_owned()));
+    let result = rt.block_on(catch_panic(async { panic!("static") }));
+    assert_eq!(result, Err::<(), _>("static".to_owned()));
+}
@@ -0,0 +1,108 @@
+use eframe::egui::{self, ColorImage};
+use eframe_tokio_app::{
+    texture::TextureImage,
+    utils::{FetchPhase, FetchTiming, ImageSource, NetworkImage},
+    CancelReason, FetchError,
+};
+use std::time::{Duration, Instant};
+
+fn texture() -> (TextureImage, ColorImage) {
+    let pixels = ColorImage::new([2, 2], egui::Color32::RED);
+    let image = TextureImage::from_color_image(&egui::Context::default(), "test", pixels.clone());
+    (image, pixels)
+}
+
+#[test]
+fn small_file_size_is_kept_in_bytes() {
+    let mut net_image = NetworkImage::default();
+    net_image.start_download();
+    assert_eq!(net_image.phase, FetchPhase::Downloading);
+    net_image.add_bytes(300);
+    net_image.add_bytes(200);
+    net_image.set_decoding();
+    assert!(net_image.phase.is_busy());
+    let (image, pixels) = texture();
+    net_image.set_image(image, pixels, 0);
+    net_image.repair();
+    assert_eq!(net_image.phase, FetchPhase::Done);
+    assert_eq!(net_image.image_size(), Some([2, 2]));
+    assert_eq!(net_image.file_size, 500);
+    assert_eq!(net_image.tmp_file_size, 0);
+}
+
+#[test]
+fn failed_fetch_keeps_the_previous_size() {
+    let mut net_image = NetworkImage::default();
+    net_image.start_download();
+    net_image.add_bytes(2048);
+    let (image, pixels) = texture();
+    net_image.set_image(image, pixels, 0);
+    net_image.repair();
+
+    net_image.start_download();
+    net_image.add_bytes(100);
+    net_image.set_error(FetchError::Canceled {
+        reason: CancelReason::UserRequested,
+    });
+    net_image.repair();
+    assert_eq!(net_image.phase, FetchPhase::Error);
+    assert_eq!(net_image.file_size, 2048);
+    assert!(net_image.image.is_some());
+    assert!(net_image.error.is_some());
+
+    // Starting again clears the error.
+    net_image.start_download();
+    assert!(net_image.error.is_none());
+}
+
+#[test]
+fn finishing_without_an_image_ends_done() {
+    let mut net_image = NetworkImage::default();
+    net_image.start_download();
+    net_image.add_bytes(10);
+    net_image.repair();
+    assert_eq!(net_image.phase, FetchPhase::Done);
+    assert_eq!(net_image.file_size, 0);
+    assert!(!net_image.stalled);
+}
+
+// What's left of the running fetch, none of it should survive its end.
+fn assert_no_progress(net_image: &NetworkImage) {
+    assert_eq!(net_image.tmp_file_size, 0);
+    assert_eq!(net_image.tmp_total, None);
+    assert_eq!(net_image.tmp_timing, None);
+    assert_eq!(net_image.tmp_source, ImageSource::Network);
+    assert!(net_image.preview.is_none());
+    assert!(!net_image.stalled);
+    assert!(!net_image.is_rate_limited());
+}
+
+#[test]
+fn canceling_mid_download_leaves_no_progress_behind() {
+    let reasons = [
+        CancelReason::UserRequested,
+        CancelReason::Timeout(Duration::from_secs(45)),
+        CancelReason::Shutdown,
+        CancelReason::Superseded,
+    ];
+    for reason in reasons {
+        let mut net_image = NetworkImage::default();
+        net_image.start_download();
+        net_image.add_bytes(4096);
+        net_image.tmp_total = Some(8192);
+        net_image.tmp_timing = Some(FetchTiming::default());
+        net_image.tmp_source = ImageSource::Cache;
+        net_image.preview = Some(texture().0);
+        net_image.stalled = true;
+        net_image.rate_limited_until = Some(Instant::now() + Duration::from_secs(60));
+
+        let error = FetchError::Canceled { reason };
+        if !error.is_deliberate_cancel() {
+            net_image.set_error(error);
+        }
+        net_image.repair();
+        assert_no_progress(&net_image);
+        assert!(!net_image.phase.is_busy(), "{:?}", reason);
+        assert_eq!(net_image.file_size, 0);
+    }
+}
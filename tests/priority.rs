@@ -0,0 +1,67 @@
+use eframe_tokio_app::priority::{Priority, PriorityGate};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+
+#[test]
+fn user_fetch_goes_before_a_waiting_background_one() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let gate = Arc::new(PriorityGate::new());
+        let limiter = Arc::new(Semaphore::new(1));
+        let held = limiter.clone().acquire_owned().await.unwrap();
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let spawn = |priority| {
+            let (gate, limiter, order) = (gate.clone(), limiter.clone(), order.clone());
+            tokio::spawn(async move {
+                let _permit = gate.acquire(limiter, priority).await.unwrap();
+                order.lock().unwrap().push(priority);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            })
+        };
+        // The background fetch is first in the limiter's queue.
+        let background = spawn(Priority::Background);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let user = spawn(Priority::User);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(gate.user_waiting(), 1);
+
+        drop(held);
+        user.await.unwrap();
+        background.await.unwrap();
+        assert_eq!(
+            *order.lock().unwrap(),
+            [Priority::User, Priority::Background]
+        );
+        assert_eq!(gate.user_waiting(), 0);
+        assert_eq!(limiter.available_permits(), 1);
+    });
+}
+
+#[test]
+fn canceled_user_fetch_stops_holding_background_ones_back() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let gate = Arc::new(PriorityGate::new());
+        let limiter = Arc::new(Semaphore::new(1));
+        let held = limiter.clone().acquire_owned().await.unwrap();
+
+        let user = {
+            let (gate, limiter) = (gate.clone(), limiter.clone());
+            tokio::spawn(async move { gate.acquire(limiter, Priority::User).await.map(drop) })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(gate.user_waiting(), 1);
+        user.abort();
+        let _ = user.await;
+        assert_eq!(gate.user_waiting(), 0);
+
+        drop(held);
+        let acquire = gate.acquire(limiter, Priority::Background);
+        let permit = tokio::time::timeout(Duration::from_secs(1), acquire).await;
+        assert!(matches!(permit, Ok(Ok(_))));
+    });
+}
@@ -0,0 +1,61 @@
+use eframe_tokio_app::{progress::ProgressQueue, utils::Channel};
+
+#[test]
+fn messages_come_out_in_order_until_full() {
+    let queue = ProgressQueue::new(4);
+    queue.push(Channel::ImageTotal(30));
+    queue.push(Channel::Image(10));
+    queue.push(Channel::Image(20));
+    assert_eq!(queue.len(), 3);
+    assert!(matches!(
+        queue.drain()[..],
+        [
+            Channel::ImageTotal(30),
+            Channel::Image(10),
+            Channel::Image(20)
+        ]
+    ));
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn full_queue_sums_up_the_oldest_byte_counts() {
+    let queue = ProgressQueue::new(3);
+    for bytes in [1, 2, 4, 8, 16] {
+        queue.push(Channel::Image(bytes));
+    }
+    assert_eq!(queue.len(), 3);
+    // Nothing is lost, only coarser.
+    assert!(matches!(
+        queue.drain()[..],
+        [Channel::Image(7), Channel::Image(8), Channel::Image(16)]
+    ));
+
+    let queue = ProgressQueue::new(3);
+    queue.push(Channel::Data(1));
+    queue.push(Channel::Image(2));
+    queue.push(Channel::Data(4));
+    queue.push(Channel::ImageDecoding);
+    // No byte counts of a kind next to each other, the oldest message goes.
+    assert!(matches!(
+        queue.drain()[..],
+        [Channel::Image(2), Channel::Data(4), Channel::ImageDecoding]
+    ));
+}
+
+#[test]
+fn stall_notices_go_before_state_changes() {
+    let queue = ProgressQueue::new(3);
+    queue.push(Channel::ImageDecoding);
+    queue.push(Channel::ImageStalled);
+    queue.push(Channel::ImageDecoded);
+    queue.push(Channel::ImageCached);
+    assert!(matches!(
+        queue.drain()[..],
+        [
+            Channel::ImageDecoding,
+            Channel::ImageDecoded,
+            Channel::ImageCached
+        ]
+    ));
+}
@@ -0,0 +1,17 @@
+use eframe_tokio_app::provider::{ImageProvider, LocalProvider, PicsumProvider};
+
+#[test]
+fn providers_build_seed_urls() {
+    assert_eq!(
+        PicsumProvider.url_for(42, 512),
+        "https://picsum.photos/seed/42/512"
+    );
+    let local = LocalProvider {
+        base_url: "http://localhost:9000/".into(),
+    };
+    assert_eq!(local.url_for(7, 256), "http://localhost:9000/seed/7/256");
+    assert_eq!(
+        LocalProvider::default().url_for(1, 64),
+        "http://127.0.0.1:8000/seed/1/64"
+    );
+}
@@ -0,0 +1,19 @@
+use eframe_tokio_app::rate_limit::HostRateLimiter;
+use std::time::Duration;
+
+#[test]
+fn slots_are_spaced_per_host() {
+    let limiter = HostRateLimiter::default();
+    let interval = Duration::from_millis(100);
+    assert_eq!(limiter.reserve("https://a.test/1", 10.0), Duration::ZERO);
+    let second = limiter.reserve("https://a.test/2", 10.0);
+    let third = limiter.reserve("https://a.test/3", 10.0);
+    assert!(second > interval - Duration::from_millis(10) && second <= interval);
+    assert!(third > second + interval - Duration::from_millis(10));
+
+    // Other hosts have their own bucket.
+    assert_eq!(limiter.reserve("https://b.test/1", 10.0), Duration::ZERO);
+    // No host or no limit, no wait.
+    assert_eq!(limiter.reserve("not a url", 10.0), Duration::ZERO);
+    assert_eq!(limiter.reserve("https://a.test/4", 0.0), Duration::ZERO);
+}
@@ -0,0 +1,33 @@
+use eframe_tokio_app::{
+    scheme::{scheme_of, SchemeRegistry},
+    FetchError,
+};
+
+#[test]
+fn schemes_are_read_from_the_url() {
+    assert_eq!(scheme_of("https://picsum.photos/200"), Some("https"));
+    assert_eq!(scheme_of("data:image/png;base64,AAAA"), Some("data"));
+    assert_eq!(scheme_of("git+ssh://host/repo"), Some("git+ssh"));
+    assert_eq!(scheme_of("picsum.photos/200"), None);
+    assert_eq!(scheme_of("://nothing"), None);
+    assert_eq!(scheme_of("1up://x"), None);
+}
+
+#[test]
+fn http_is_left_to_the_client_and_unknown_schemes_fail() {
+    let mut schemes = SchemeRegistry::default();
+    for url in ["http://a/b.png", "HTTPS://a/b.png", "no-scheme.png"] {
+        assert!(schemes.handler_for(url).unwrap().is_none(), "{}", url);
+    }
+    for url in ["file:///tmp/a.png", "data:,x", "Data:,x"] {
+        assert!(schemes.handler_for(url).unwrap().is_some(), "{}", url);
+    }
+    assert!(matches!(
+        schemes.handler_for("mem://a.png"),
+        Err(FetchError::UnsupportedScheme(scheme)) if scheme == "mem"
+    ));
+
+    schemes.register("MEM", |_: &str| Ok(vec![1, 2, 3]));
+    let handler = schemes.handler_for("mem://a.png").unwrap().unwrap();
+    assert_eq!(handler("mem://a.png").unwrap(), [1, 2, 3]);
+}
@@ -0,0 +1,128 @@
+use eframe::Storage;
+use eframe_tokio_app::{
+    settings::{Settings, StartupStorage},
+    texture::ResizeFilter,
+    FetchConfig,
+};
+use std::{collections::HashMap, time::Duration};
+
+#[derive(Default)]
+struct MemoryStorage(HashMap<String, String>);
+
+impl Storage for MemoryStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.0.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_owned(), value);
+    }
+
+    fn flush(&mut self) {}
+}
+
+#[test]
+fn settings_round_trip_through_storage() {
+    let settings = Settings {
+        max_concurrent: 5,
+        max_image_mb: 12,
+        deadline_secs: 90,
+        offline: true,
+        prefer_webp: false,
+        auto_retry: true,
+        auto_retry_attempts: 7,
+        provider: 1,
+        proxy: "socks5://127.0.0.1:1080".into(),
+        accept_invalid_certs: true,
+        show_hud: true,
+        show_spinner: false,
+        sharp_downscale: true,
+        downscale_filter: ResizeFilter::CatmullRom,
+        ..Default::default()
+    };
+    let mut storage = MemoryStorage::default();
+    settings.save(&mut storage);
+    assert_eq!(Settings::load(Some(&storage)), settings);
+}
+
+#[test]
+fn missing_settings_fall_back_to_defaults() {
+    assert_eq!(Settings::load(None), Settings::default());
+    let mut storage = MemoryStorage::default();
+    // Saved by an older version, with fewer fields.
+    storage.set_string(Settings::STORAGE_KEY, "(max_concurrent: 4)".into());
+    let settings = Settings::load(Some(&storage));
+    assert_eq!(settings.max_concurrent, 4);
+    assert_eq!(settings.deadline_secs, Settings::default().deadline_secs);
+}
+
+#[test]
+fn settings_apply_to_fetch_config() {
+    let settings = Settings {
+        max_image_mb: 3,
+        deadline_secs: 20,
+        repaint_interval_ms: 0,
+        prefer_webp: !FetchConfig::default().prefers_webp(),
+        ..Default::default()
+    };
+    let mut config = FetchConfig::default();
+    settings.apply_to(&mut config);
+    assert_eq!(config.max_image_bytes, 3 * 1024 * 1024);
+    assert_eq!(config.deadline, Duration::from_secs(20));
+    assert_eq!(config.repaint_interval, Duration::ZERO);
+    assert_eq!(config.prefers_webp(), settings.prefer_webp);
+    assert_eq!(config.min_dimensions, None);
+    Settings {
+        min_width: 512,
+        ..Default::default()
+    }
+    .apply_to(&mut config);
+    assert_eq!(config.min_dimensions, Some((512, 0)));
+    assert_eq!(
+        Settings {
+            proxy: "  ".into(),
+            ..Default::default()
+        }
+        .proxy(),
+        None
+    );
+}
+
+#[test]
+fn ui_scale_follows_the_display_unless_overridden() {
+    let mut settings = Settings::default();
+    assert_eq!(settings.pixels_per_point(Some(1.5)), 1.5);
+    assert_eq!(settings.pixels_per_point(None), 1.0);
+    settings.ui_scale_percent = 125;
+    assert_eq!(settings.pixels_per_point(Some(2.0)), 1.25);
+    // Too small to be usable.
+    settings.ui_scale_percent = 10;
+    assert_eq!(settings.pixels_per_point(Some(2.0)), 0.5);
+}
+
+#[test]
+fn startup_storage_reads_the_eframe_file() {
+    let settings = Settings {
+        always_on_top: true,
+        decorations: false,
+        ..Default::default()
+    };
+    // Written like eframe does: a RON map of RON encoded values.
+    let mut storage = MemoryStorage::default();
+    settings.save(&mut storage);
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ron");
+    std::fs::write(&path, ron::to_string(&storage.0).unwrap()).unwrap();
+    let startup = StartupStorage::from_ron_file(&path);
+    assert_eq!(Settings::load(Some(&startup)), settings);
+
+    // First launch, or a file that can't be read.
+    let missing = StartupStorage::from_ron_file(dir.path().join("missing.ron"));
+    std::fs::write(&path, "not ron").unwrap();
+    let garbage = StartupStorage::from_ron_file(&path);
+    for storage in [missing, garbage] {
+        let settings = Settings::load(Some(&storage));
+        assert!(!settings.always_on_top);
+        assert!(settings.decorations);
+    }
+}
@@ -0,0 +1,237 @@
+use eframe::egui;
+use eframe_tokio_app::texture::{
+    color_hex, display_texture, downscale_target, pixel_at, requested_image_size, texture_memory,
+    Crossfade, DisplayCopy, FitMode, ResizeFilter, TextureImage, MAX_IMAGE_SIZE, MIN_IMAGE_SIZE,
+};
+use image::{ImageOutputFormat, Rgba, RgbaImage};
+use std::{
+    io::Cursor,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn small_png_loads_into_texture() {
+    let mut png_bytes = Vec::new();
+    RgbaImage::from_pixel(3, 2, Rgba([255, 0, 0, 255]))
+        .write_to(&mut Cursor::new(&mut png_bytes), ImageOutputFormat::Png)
+        .unwrap();
+
+    let ctx = egui::Context::default();
+    let image = TextureImage::from_image_bytes(&ctx, "small.png", &png_bytes).unwrap();
+    assert_eq!(image.debug_name(), "small.png");
+    assert_eq!((image.width(), image.height()), (3, 2));
+    assert_eq!(image.size_vec2(), egui::vec2(3.0, 2.0));
+}
+
+#[test]
+fn fit_modes_scale_to_the_available_space() {
+    let ppp = 2.0;
+    let image = egui::vec2(800.0, 400.0);
+    // 400x200 points at 100%.
+    let cases = [
+        (
+            FitMode::ActualSize,
+            egui::vec2(100.0, 100.0),
+            egui::vec2(400.0, 200.0),
+        ),
+        // Limited by the width, then by the height.
+        (
+            FitMode::Window,
+            egui::vec2(200.0, 500.0),
+            egui::vec2(200.0, 100.0),
+        ),
+        (
+            FitMode::Window,
+            egui::vec2(1000.0, 50.0),
+            egui::vec2(100.0, 50.0),
+        ),
+        // Scaled up to fill the room.
+        (
+            FitMode::Window,
+            egui::vec2(1200.0, 1200.0),
+            egui::vec2(1200.0, 600.0),
+        ),
+        // The height doesn't matter, it scrolls.
+        (
+            FitMode::Width,
+            egui::vec2(200.0, 10.0),
+            egui::vec2(200.0, 100.0),
+        ),
+        (
+            FitMode::Width,
+            egui::vec2(800.0, 10.0),
+            egui::vec2(800.0, 400.0),
+        ),
+    ];
+    for (mode, available, expected) in cases {
+        assert_eq!(mode.display_size(image, available, ppp), expected);
+    }
+
+    // No room to go by.
+    for available in [egui::Vec2::ZERO, egui::Vec2::INFINITY] {
+        assert_eq!(
+            FitMode::Window.display_size(image, available, ppp),
+            egui::vec2(400.0, 200.0)
+        );
+    }
+    assert_eq!(FitMode::default(), FitMode::ActualSize);
+}
+
+#[test]
+fn display_size_follows_the_ui_scale() {
+    let image = egui::vec2(600.0, 300.0);
+    let available = egui::vec2(300.0, 300.0);
+    for ppp in [1.0, 1.25, 2.0] {
+        // Pixel for pixel on screen, whatever the scale.
+        let actual = FitMode::ActualSize.display_size(image, available, ppp);
+        assert_eq!(actual * ppp, image, "at {}", ppp);
+        // Fitting only depends on the room.
+        assert_eq!(
+            FitMode::Window.display_size(image, available, ppp),
+            egui::vec2(300.0, 150.0),
+            "at {}",
+            ppp
+        );
+    }
+}
+
+#[test]
+fn screen_positions_map_to_image_pixels() {
+    let size = [4, 2];
+    // Shown at twice its size, scrolled so it starts left of and above the screen origin.
+    let rect = egui::Rect::from_min_size(egui::pos2(-2.0, -1.0), egui::vec2(8.0, 4.0));
+    assert_eq!(pixel_at(egui::pos2(-2.0, -1.0), rect, size), Some([0, 0]));
+    assert_eq!(pixel_at(egui::pos2(-0.1, 0.9), rect, size), Some([0, 0]));
+    assert_eq!(pixel_at(egui::pos2(0.0, 1.0), rect, size), Some([1, 1]));
+    assert_eq!(pixel_at(egui::pos2(3.9, 0.5), rect, size), Some([2, 0]));
+    // The far edges are the last pixels, past them is outside.
+    assert_eq!(pixel_at(rect.max, rect, size), Some([3, 1]));
+    assert_eq!(pixel_at(egui::pos2(6.1, 0.0), rect, size), None);
+    assert_eq!(pixel_at(egui::pos2(0.0, -1.1), rect, size), None);
+
+    // Shrunk to half its size.
+    let rect = egui::Rect::from_min_size(egui::pos2(10.0, 10.0), egui::vec2(2.0, 1.0));
+    assert_eq!(pixel_at(egui::pos2(11.6, 10.4), rect, size), Some([3, 0]));
+
+    // Nothing to pick from an empty image or rect.
+    assert_eq!(pixel_at(egui::pos2(10.0, 10.0), rect, [0, 0]), None);
+    let empty = egui::Rect::from_min_size(egui::pos2(10.0, 10.0), egui::Vec2::ZERO);
+    assert_eq!(pixel_at(egui::pos2(10.0, 10.0), empty, size), None);
+}
+
+#[test]
+fn colors_show_as_unmultiplied_hex() {
+    assert_eq!(color_hex(egui::Color32::from_rgb(255, 128, 0)), "#ff8000ff");
+    assert_eq!(
+        color_hex(egui::Color32::from_rgba_unmultiplied(255, 0, 0, 128)),
+        "#ff000080"
+    );
+}
+
+#[test]
+fn texture_memory_counts_four_bytes_per_texel() {
+    assert_eq!(texture_memory([]), 0);
+    assert_eq!(texture_memory([[512, 512]]), 1024 * 1024);
+    assert_eq!(
+        texture_memory([[512, 512], [64, 32], [1, 1]]),
+        1024 * 1024 + 8192 + 4
+    );
+    // Empty textures take nothing.
+    assert_eq!(texture_memory([[0, 512], [512, 0]]), 0);
+}
+
+#[test]
+fn window_sizes_map_to_requested_image_sizes() {
+    // The shorter side, rounded to the nearest step.
+    assert_eq!(requested_image_size(egui::vec2(1000.0, 720.0)), 768);
+    assert_eq!(requested_image_size(egui::vec2(720.0, 1000.0)), 768);
+    assert_eq!(requested_image_size(egui::vec2(1000.0, 570.0)), 512);
+    assert_eq!(requested_image_size(egui::vec2(1000.0, 580.0)), 640);
+    // A few pixels either way keep the same size.
+    assert_eq!(requested_image_size(egui::vec2(800.0, 760.0)), 768);
+    assert_eq!(requested_image_size(egui::vec2(800.0, 780.0)), 768);
+    // Capped both ways.
+    assert_eq!(requested_image_size(egui::vec2(0.0, 0.0)), MIN_IMAGE_SIZE);
+    assert_eq!(
+        requested_image_size(egui::vec2(-10.0, 50.0)),
+        MIN_IMAGE_SIZE
+    );
+    assert_eq!(
+        requested_image_size(egui::vec2(8000.0, 6000.0)),
+        MAX_IMAGE_SIZE
+    );
+}
+
+#[test]
+fn crossfade_progresses_and_completes() {
+    let ctx = egui::Context::default();
+    let previous = TextureImage::from_color_image(
+        &ctx,
+        "previous",
+        egui::ColorImage::new([2, 2], egui::Color32::RED),
+    );
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let fade = Crossfade::new(previous, Duration::from_millis(200), start);
+    assert_eq!(fade.previous().debug_name(), "previous");
+
+    assert_eq!(fade.opacity(start), 0.0);
+    assert!(!fade.is_done(at(0)));
+    let mut last = 0.0;
+    for ms in [20, 50, 100, 150, 190] {
+        let opacity = fade.opacity(at(ms));
+        assert!(opacity > last && opacity < 1.0, "{} at {}ms", opacity, ms);
+        last = opacity;
+    }
+    // Eased, halfway in time is halfway there.
+    assert!((fade.opacity(at(100)) - 0.5).abs() < 1e-6);
+    assert_eq!(fade.opacity(at(200)), 1.0);
+    assert!(fade.is_done(at(200)));
+    assert_eq!(fade.opacity(at(5000)), 1.0);
+
+    let instant = Crossfade::new(fade.previous().clone(), Duration::ZERO, start);
+    assert!(instant.is_done(start));
+}
+
+#[test]
+fn images_shown_smaller_are_downscaled_to_the_shown_size() {
+    let image = [800, 400];
+    assert_eq!(
+        downscale_target(image, egui::vec2(200.0, 100.0)),
+        Some([200, 100])
+    );
+    // Rounded up, the aspect ratio kept.
+    assert_eq!(
+        downscale_target(image, egui::vec2(333.3, 166.6)),
+        Some([334, 167])
+    );
+    // Near or past the actual size, the texture filtering does fine.
+    assert_eq!(downscale_target(image, egui::vec2(760.0, 380.0)), None);
+    assert_eq!(downscale_target(image, egui::vec2(1600.0, 800.0)), None);
+    assert_eq!(downscale_target([0, 0], egui::vec2(10.0, 10.0)), None);
+}
+
+#[test]
+fn display_copy_is_the_target_size_and_zooming_in_shows_the_original() {
+    let ctx = egui::Context::default();
+    let pixels = egui::ColorImage::new([400, 200], egui::Color32::RED);
+    let original = TextureImage::from_color_image(&ctx, "big.png", pixels.clone());
+    let target = downscale_target(original.size(), egui::vec2(100.0, 50.0)).unwrap();
+    let copy = DisplayCopy::new(&ctx, &original, &pixels, target, ResizeFilter::Lanczos3);
+    assert_eq!(copy.image().size(), [100, 50]);
+    assert_eq!(copy.source(), original.id());
+    assert_eq!(copy.filter(), ResizeFilter::Lanczos3);
+
+    let shown = |size: egui::Vec2| display_texture(&original, Some(&copy), size).id();
+    assert_eq!(shown(egui::vec2(100.0, 50.0)), copy.image().id());
+    assert_eq!(shown(egui::vec2(80.0, 40.0)), copy.image().id());
+    // Zoomed in past the copy, the original is still there.
+    assert_eq!(shown(egui::vec2(300.0, 150.0)), original.id());
+    assert_eq!(original.size(), [400, 200]);
+    // A copy of another image doesn't stand for this one.
+    let other = TextureImage::from_color_image(&ctx, "other.png", pixels);
+    assert_eq!(
+        display_texture(&other, Some(&copy), egui::vec2(100.0, 50.0)).id(),
+        other.id()
+    );
+}
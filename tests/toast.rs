@@ -0,0 +1,61 @@
+use eframe_tokio_app::toast::{ToastKind, Toasts, MAX_TOASTS, TOAST_FADE};
+use std::time::{Duration, Instant};
+
+#[test]
+fn toasts_expire_after_their_duration() {
+    let duration = Duration::from_secs(2);
+    let mut toasts = Toasts::new(duration);
+    let start = Instant::now();
+    toasts.push_at(ToastKind::Success, "Saved.", start);
+    toasts.push_at(
+        ToastKind::Error,
+        "Fetch failed.",
+        start + Duration::from_secs(1),
+    );
+
+    let up = |toasts: &Toasts, at| toasts.visible(at).count();
+    assert_eq!(up(&toasts, start), 2);
+    assert_eq!(up(&toasts, start + duration - Duration::from_millis(1)), 2);
+    assert_eq!(up(&toasts, start + duration), 1);
+    assert_eq!(up(&toasts, start + Duration::from_secs(3)), 0);
+
+    toasts.expire(start + duration);
+    let left: Vec<_> = toasts.visible(start + duration).collect();
+    assert_eq!(left.len(), 1);
+    assert_eq!(
+        (left[0].kind, left[0].text.as_str()),
+        (ToastKind::Error, "Fetch failed.")
+    );
+    toasts.expire(start + Duration::from_secs(3));
+    assert!(toasts.is_empty());
+}
+
+#[test]
+fn toasts_fade_out_at_the_end() {
+    let duration = Duration::from_secs(2);
+    let mut toasts = Toasts::new(duration);
+    let start = Instant::now();
+    toasts.push_at(ToastKind::Info, "Retrying…", start);
+    let toast = toasts.visible(start).next().unwrap().clone();
+
+    assert_eq!(toast.opacity(start), 1.0);
+    assert_eq!(toast.opacity(start + duration - TOAST_FADE), 1.0);
+    let halfway = toast.opacity(start + duration - TOAST_FADE / 2);
+    assert!((halfway - 0.5).abs() < 1e-3, "{}", halfway);
+    assert_eq!(toast.opacity(start + duration), 0.0);
+}
+
+#[test]
+fn oldest_toasts_make_room() {
+    let mut toasts = Toasts::default();
+    let now = Instant::now();
+    for i in 0..MAX_TOASTS + 2 {
+        toasts.push_at(ToastKind::Info, i, now);
+    }
+    let texts: Vec<_> = toasts
+        .visible(now)
+        .map(|toast| toast.text.clone())
+        .collect();
+    assert_eq!(texts.len(), MAX_TOASTS);
+    assert_eq!(texts[0], "2");
+}
@@ -0,0 +1,176 @@
+use eframe_tokio_app::{
+    utils::{human_bytes, random_seed, Debounce, Direction, SeedHistory, SizeEstimator, UiMode},
+    FetchError,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::time::{Duration, Instant};
+
+#[test]
+fn human_bytes_across_ranges() {
+    assert_eq!(human_bytes(0), "0 B");
+    assert_eq!(human_bytes(999), "999 B");
+    assert_eq!(human_bytes(1023), "1023 B");
+    assert_eq!(human_bytes(1024), "1.0 KB");
+    assert_eq!(human_bytes(1536), "1.5 KB");
+    assert_eq!(human_bytes(1024 * 1024 - 1), "1024.0 KB");
+    assert_eq!(human_bytes(1024 * 1024), "1.0 MB");
+    assert_eq!(human_bytes(4_404_019), "4.2 MB");
+    assert_eq!(human_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+    assert_eq!(human_bytes(2048 * 1024 * 1024 * 1024), "2048.0 GB");
+}
+
+#[test]
+fn size_estimate_is_a_rolling_average_capped_below_done() {
+    let mut estimates = SizeEstimator::default();
+    assert_eq!(estimates.estimate(512), None);
+    assert_eq!(estimates.fraction(512, 1000), None);
+
+    estimates.record(512, 1000);
+    estimates.record(512, 3000);
+    // Empty downloads say nothing about the size.
+    estimates.record(512, 0);
+    assert_eq!(estimates.estimate(512), Some(2000));
+    assert_eq!(estimates.fraction(512, 500), Some(0.25));
+    // Past the estimate it stays short of done.
+    assert_eq!(
+        estimates.fraction(512, 5000),
+        Some(SizeEstimator::MAX_FRACTION)
+    );
+    // Each requested size has its own history.
+    assert_eq!(estimates.estimate(1024), None);
+
+    // Only the last samples count.
+    for _ in 0..SizeEstimator::MAX_SAMPLES {
+        estimates.record(512, 100);
+    }
+    assert_eq!(estimates.estimate(512), Some(100));
+}
+
+#[test]
+fn debounce_waits_for_the_value_to_settle() {
+    let delay = Duration::from_millis(500);
+    let mut size = Debounce::new(512, delay);
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+
+    // Still resizing, every new value starts the wait over.
+    assert_eq!(size.update(640, at(0)), 512);
+    assert_eq!(size.update(768, at(300)), 512);
+    assert_eq!(size.update(768, at(700)), 512);
+    assert!(size.is_pending());
+    assert_eq!(size.update(768, at(800)), 768);
+    assert!(!size.is_pending());
+
+    // Back to the settled value before the delay, nothing changes.
+    assert_eq!(size.update(1024, at(900)), 768);
+    assert_eq!(size.update(768, at(1000)), 768);
+    assert_eq!(size.update(1024, at(1600)), 768);
+    assert_eq!(size.value(), 768);
+}
+
+#[test]
+fn random_seeds_stay_in_range_and_never_repeat() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut seed = 1;
+    for _ in 0..1000 {
+        let next = random_seed(1..=5, seed, &mut rng);
+        assert!((1..=5).contains(&next));
+        assert_ne!(next, seed);
+        seed = next;
+    }
+    // Both ends can come up.
+    let picks: Vec<_> = (0..200).map(|_| random_seed(1..=3, 2, &mut rng)).collect();
+    assert!(picks.contains(&1) && picks.contains(&3));
+    // With a single seed there's no other choice.
+    assert_eq!(random_seed(4..=4, 4, &mut rng), 4);
+}
+
+#[test]
+fn seed_history_goes_back_through_random_picks() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut seeds = SeedHistory::default();
+    let first = seeds.random(12, 1..=1000, &mut rng);
+    let second = seeds.random(first, 1..=1000, &mut rng);
+
+    // Prev retraces the picks, then counts down.
+    assert_eq!(seeds.prev(second, Some(second - 1)), Some(first));
+    assert_eq!(seeds.prev(first, Some(first - 1)), Some(12));
+    assert_eq!(seeds.prev(12, Some(11)), Some(11));
+    assert_eq!(seeds.prev(1, None), None);
+    // Next replays them, then counts up.
+    assert_eq!(seeds.next(11, Some(12)), Some(12));
+    assert_eq!(seeds.next(12, Some(13)), Some(first));
+    assert_eq!(seeds.next(first, Some(first + 1)), Some(second));
+    assert_eq!(seeds.next(second, Some(second + 1)), Some(second + 1));
+
+    // A failed move leaves things as they were, and can be tried again.
+    assert_eq!(seeds.next(second + 1, None), None);
+    let third = seeds.random(second + 1, 1..=1000, &mut rng);
+    assert_eq!(seeds.undo(true, third), Some(second + 1));
+    assert_eq!(seeds.next(second + 1, None), Some(third));
+    assert_eq!(seeds.prev(third, None), Some(second + 1));
+    assert_eq!(seeds.undo(false, second + 1), Some(third));
+}
+
+#[test]
+fn ui_mode_follows_the_fetch_and_labels_its_button() {
+    let labels = |mode: UiMode| (mode.label(Direction::Prev), mode.label(Direction::Next));
+    assert_eq!(
+        labels(UiMode::Idle),
+        ("Fetch prev image".into(), "Fetch next image".into())
+    );
+
+    // Fetching keeps the labels, only the finalized fetch changes them.
+    let fetching = UiMode::Fetching(Direction::Next);
+    assert_eq!(labels(fetching), labels(UiMode::Idle));
+    assert_eq!(fetching.direction(), Some(Direction::Next));
+
+    let canceled = UiMode::finished(Direction::Next, true, None);
+    assert_eq!(canceled, UiMode::Canceled(Direction::Next));
+    assert_eq!(
+        labels(canceled),
+        ("Fetch prev image".into(), "Retry next image?".into())
+    );
+
+    // Canceled wins over the error it was canceled with.
+    let error = FetchError::NotFound;
+    assert_eq!(
+        UiMode::finished(Direction::Prev, true, Some(&error)),
+        UiMode::Canceled(Direction::Prev)
+    );
+    let not_found = UiMode::finished(Direction::Prev, false, Some(&error));
+    assert_eq!(not_found, UiMode::NotFound(Direction::Prev));
+    assert_eq!(not_found.label(Direction::Prev), "No image for this seed");
+    assert_eq!(not_found.label(Direction::Next), "Fetch next image");
+
+    // Other errors show in the banner, the buttons go back to idle.
+    let reset = FetchError::Other("connection reset".into());
+    assert_eq!(
+        UiMode::finished(Direction::Next, false, Some(&reset)),
+        UiMode::Idle
+    );
+    assert_eq!(UiMode::finished(Direction::Next, false, None), UiMode::Idle);
+
+    let retrying = UiMode::Retrying {
+        direction: Direction::Next,
+        attempt: 2,
+        max: 3,
+    };
+    assert_eq!(retrying.label(Direction::Next), "Retrying (2/3)...");
+    let rate_limited = UiMode::RateLimited {
+        direction: Direction::Prev,
+        secs: 5,
+    };
+    assert_eq!(
+        rate_limited.label(Direction::Prev),
+        "Rate limited, retrying in 5s..."
+    );
+    let unavailable = UiMode::Unavailable(Direction::Next);
+    assert_eq!(
+        unavailable.label(Direction::Next),
+        "Next image not available"
+    );
+    assert!(!unavailable.is_enabled(Direction::Next));
+    assert!(unavailable.is_enabled(Direction::Prev));
+    assert!(canceled.is_enabled(Direction::Next));
+}
@@ -0,0 +1,54 @@
+use eframe::egui;
+use eframe_tokio_app::{texture::TextureImage, viewer::ImageWindows};
+
+fn textures(ctx: &egui::Context) -> usize {
+    ctx.tex_manager().read().num_allocated()
+}
+
+#[test]
+fn closing_windows_frees_their_textures() {
+    let ctx = egui::Context::default();
+    let before = textures(&ctx);
+    let mut windows = ImageWindows::default();
+    let image = |name| {
+        let pixels = egui::ColorImage::new([4, 3], egui::Color32::RED);
+        TextureImage::from_color_image(&ctx, name, pixels)
+    };
+    let first = windows.open("a.png", image("a.png"));
+    let second = windows.open("b.png", image("b.png"));
+    assert_ne!(first, second);
+    assert_eq!(windows.len(), 2);
+    assert_eq!(textures(&ctx), before + 2);
+
+    // Painting them doesn't close them.
+    let _ = ctx.run(Default::default(), |ctx| windows.show(ctx));
+    assert_eq!(windows.len(), 2);
+
+    windows.close(first);
+    // Already closed, nothing happens.
+    windows.close(first);
+    let titles: Vec<_> = windows.iter().map(|window| window.title()).collect();
+    assert_eq!(titles, ["b.png"]);
+    assert_eq!(textures(&ctx), before + 1);
+    windows.close(second);
+    assert!(windows.is_empty());
+    assert_eq!(textures(&ctx), before);
+
+    // Ids aren't reused, a late close can't hit a newer window.
+    let third = windows.open("c.png", image("c.png"));
+    assert!(third > second);
+}
+
+#[test]
+fn each_window_has_its_own_zoom() {
+    let ctx = egui::Context::default();
+    let pixels = egui::ColorImage::new([4, 3], egui::Color32::RED);
+    let image = TextureImage::from_color_image(&ctx, "a.png", pixels);
+    let mut windows = ImageWindows::default();
+    let first = windows.open("a.png", image.clone());
+    let second = windows.open("a.png", image);
+    windows.get_mut(first).unwrap().set_zoom(2.0);
+    windows.get_mut(second).unwrap().set_zoom(100.0);
+    let zooms: Vec<_> = windows.iter().map(|window| window.zoom()).collect();
+    assert_eq!(zooms, [2.0, 8.0]);
+}